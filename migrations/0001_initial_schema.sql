@@ -0,0 +1,50 @@
+CREATE TABLE classes (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE characters (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    class_id INTEGER NOT NULL REFERENCES classes (id),
+    portrait BYTEA NOT NULL,
+    portrait_small BYTEA NOT NULL
+);
+
+CREATE TABLE skills (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    level SMALLINT NOT NULL CHECK (level BETWEEN 1 AND 3),
+    image BYTEA NOT NULL
+);
+
+CREATE TABLE specialties (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    class_id INTEGER NOT NULL REFERENCES classes (id),
+    image BYTEA NOT NULL,
+    UNIQUE (name, class_id)
+);
+
+CREATE TABLE heroes (
+    id SERIAL PRIMARY KEY,
+    character_id INTEGER NOT NULL UNIQUE REFERENCES characters (id) ON DELETE CASCADE,
+    spec_id INTEGER NOT NULL REFERENCES specialties (id),
+    attack SMALLINT NOT NULL,
+    defence SMALLINT NOT NULL,
+    power SMALLINT NOT NULL,
+    knowledge SMALLINT NOT NULL,
+    luck SMALLINT NOT NULL,
+    morale SMALLINT NOT NULL,
+    experience INTEGER NOT NULL,
+    mana_max INTEGER NOT NULL,
+    mana_current INTEGER NOT NULL,
+    level SMALLINT NOT NULL
+);
+
+CREATE TABLE hero_skills (
+    hero_id INTEGER NOT NULL REFERENCES heroes (id) ON DELETE CASCADE,
+    slot SMALLINT NOT NULL CHECK (slot BETWEEN 0 AND 7),
+    skill_id INTEGER NOT NULL REFERENCES skills (id) ON DELETE CASCADE,
+    PRIMARY KEY (hero_id, slot)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE creatures (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    town TEXT NOT NULL,
+    portrait BYTEA NOT NULL
+);
+
+CREATE TABLE hero_units (
+    hero_id INTEGER NOT NULL REFERENCES heroes (id) ON DELETE CASCADE,
+    slot SMALLINT NOT NULL CHECK (slot BETWEEN 0 AND 6),
+    creature_id INTEGER NOT NULL REFERENCES creatures (id) ON DELETE CASCADE,
+    count INTEGER NOT NULL CHECK (count > 0),
+    PRIMARY KEY (hero_id, slot)
+);
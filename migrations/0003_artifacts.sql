@@ -0,0 +1,24 @@
+CREATE TABLE items (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    kind SMALLINT NOT NULL CHECK (kind BETWEEN 0 AND 7),
+    image BYTEA NOT NULL,
+    attack SMALLINT NOT NULL DEFAULT 0,
+    defence SMALLINT NOT NULL DEFAULT 0,
+    power SMALLINT NOT NULL DEFAULT 0,
+    knowledge SMALLINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE hero_equipment (
+    hero_id INTEGER NOT NULL REFERENCES heroes (id) ON DELETE CASCADE,
+    slot SMALLINT NOT NULL CHECK (slot BETWEEN 0 AND 16),
+    item_id INTEGER NOT NULL REFERENCES items (id) ON DELETE CASCADE,
+    PRIMARY KEY (hero_id, slot)
+);
+
+CREATE TABLE hero_backpack (
+    hero_id INTEGER NOT NULL REFERENCES heroes (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL CHECK (position >= 0),
+    item_id INTEGER NOT NULL REFERENCES items (id) ON DELETE CASCADE,
+    PRIMARY KEY (hero_id, position)
+);
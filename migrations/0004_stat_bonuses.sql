@@ -0,0 +1,11 @@
+ALTER TABLE items
+    ADD COLUMN luck SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN morale SMALLINT NOT NULL DEFAULT 0;
+
+ALTER TABLE specialties
+    ADD COLUMN attack SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN defence SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN power SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN knowledge SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN luck SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN morale SMALLINT NOT NULL DEFAULT 0;
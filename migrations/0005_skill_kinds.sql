@@ -0,0 +1,40 @@
+CREATE TABLE skill_kinds (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+INSERT INTO skill_kinds (name) SELECT DISTINCT name FROM skills;
+
+ALTER TABLE skills ADD COLUMN kind_id INTEGER REFERENCES skill_kinds (id) ON DELETE CASCADE;
+UPDATE skills SET kind_id = k.id FROM skill_kinds k WHERE k.name = skills.name;
+
+-- Keep one row per kind and mastery: heroes are moved to the oldest one first.
+UPDATE hero_skills hs SET skill_id = keep.id
+FROM skills dup
+JOIN (SELECT MIN(id) AS id, kind_id, level FROM skills GROUP BY kind_id, level) keep
+    ON keep.kind_id = dup.kind_id AND keep.level = dup.level
+WHERE hs.skill_id = dup.id;
+DELETE FROM skills s USING skills keep
+WHERE keep.kind_id = s.kind_id AND keep.level = s.level AND keep.id < s.id;
+
+ALTER TABLE skills
+    ALTER COLUMN kind_id SET NOT NULL,
+    DROP COLUMN name,
+    ADD UNIQUE (kind_id, level),
+    ADD UNIQUE (id, kind_id);
+
+-- A hero holds each kind once. Duplicates keep the highest mastery.
+ALTER TABLE hero_skills ADD COLUMN kind_id INTEGER;
+UPDATE hero_skills hs SET kind_id = s.kind_id FROM skills s WHERE s.id = hs.skill_id;
+DELETE FROM hero_skills hs
+USING hero_skills other, skills s, skills so
+WHERE other.hero_id = hs.hero_id AND other.kind_id = hs.kind_id AND other.slot <> hs.slot
+    AND s.id = hs.skill_id AND so.id = other.skill_id
+    AND (so.level > s.level OR (so.level = s.level AND other.slot < hs.slot));
+
+ALTER TABLE hero_skills
+    ALTER COLUMN kind_id SET NOT NULL,
+    DROP CONSTRAINT hero_skills_skill_id_fkey,
+    ADD FOREIGN KEY (skill_id, kind_id) REFERENCES skills (id, kind_id)
+        ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD UNIQUE (hero_id, kind_id);
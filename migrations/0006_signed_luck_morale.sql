@@ -0,0 +1,7 @@
+UPDATE heroes
+SET luck = LEAST(GREATEST(luck - 3, -3), 3),
+    morale = LEAST(GREATEST(morale - 3, -3), 3);
+
+ALTER TABLE heroes
+    ADD CONSTRAINT heroes_luck_range CHECK (luck BETWEEN -3 AND 3),
+    ADD CONSTRAINT heroes_morale_range CHECK (morale BETWEEN -3 AND 3);
@@ -0,0 +1,2 @@
+-- Maxima stored so far were typed in by hand, so they stay overrides.
+ALTER TABLE heroes ADD COLUMN mana_max_manual BOOLEAN NOT NULL DEFAULT TRUE;
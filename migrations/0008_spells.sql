@@ -0,0 +1,15 @@
+CREATE TABLE spells (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    school SMALLINT NOT NULL CHECK (school BETWEEN 0 AND 3),
+    level SMALLINT NOT NULL CHECK (level BETWEEN 1 AND 5),
+    cost SMALLINT NOT NULL CHECK (cost >= 0),
+    power_factor SMALLINT NOT NULL DEFAULT 0 CHECK (power_factor >= 0),
+    image BYTEA NOT NULL
+);
+
+CREATE TABLE hero_spells (
+    hero_id INTEGER NOT NULL REFERENCES heroes (id) ON DELETE CASCADE,
+    spell_id INTEGER NOT NULL REFERENCES spells (id) ON DELETE CASCADE,
+    PRIMARY KEY (hero_id, spell_id)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE creatures
+    ADD COLUMN speed SMALLINT NOT NULL DEFAULT 4 CHECK (speed BETWEEN 1 AND 30);
@@ -0,0 +1,2 @@
+-- Heroes created from the viewer start without a specialty.
+ALTER TABLE heroes ALTER COLUMN spec_id DROP NOT NULL;
@@ -0,0 +1,41 @@
+ALTER TABLE classes
+    ADD COLUMN town TEXT NOT NULL DEFAULT 'Нейтральные',
+    ADD COLUMN is_magic BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN low_level_growth SMALLINT[] NOT NULL DEFAULT '{25,25,25,25}',
+    ADD COLUMN high_level_growth SMALLINT[] NOT NULL DEFAULT '{25,25,25,25}',
+    ADD CHECK (cardinality(low_level_growth) = 4 AND cardinality(high_level_growth) = 4);
+
+CREATE TABLE class_skill_weights (
+    class_id INTEGER NOT NULL REFERENCES classes (id) ON DELETE CASCADE,
+    skill_kind_id INTEGER NOT NULL REFERENCES skill_kinds (id) ON DELETE CASCADE,
+    weight SMALLINT NOT NULL CHECK (weight >= 0),
+    PRIMARY KEY (class_id, skill_kind_id)
+);
+
+-- The growth table that used to be hardcoded in the viewer. Classes are
+-- added here rather than by the demo seed, which runs after the migrations.
+INSERT INTO classes (name, town, is_magic, low_level_growth, high_level_growth)
+VALUES
+    ('Рыцарь', 'Замок', FALSE, '{35,45,10,10}', '{30,30,20,20}'),
+    ('Священник', 'Замок', TRUE, '{20,15,30,35}', '{20,20,30,30}'),
+    ('Рейнджер', 'Оплот', FALSE, '{35,45,10,10}', '{30,30,20,20}'),
+    ('Друид', 'Оплот', TRUE, '{10,20,35,35}', '{20,20,30,30}'),
+    ('Алхимик', 'Башня', FALSE, '{30,30,15,25}', '{30,30,20,20}'),
+    ('Маг', 'Башня', TRUE, '{10,10,40,40}', '{30,20,20,30}'),
+    ('Демонопоклонник', 'Инферно', FALSE, '{35,35,15,15}', '{25,25,25,25}'),
+    ('Еретик', 'Инферно', TRUE, '{15,15,35,35}', '{20,20,30,30}'),
+    ('Рыцарь смерти', 'Некрополис', FALSE, '{30,25,20,25}', '{25,25,25,25}'),
+    ('Некромант', 'Некрополис', TRUE, '{15,15,35,35}', '{25,25,25,25}'),
+    ('Лорд', 'Темница', FALSE, '{35,35,15,15}', '{30,30,20,20}'),
+    ('Чернокнижник', 'Темница', TRUE, '{10,10,50,30}', '{20,20,30,30}'),
+    ('Варвар', 'Цитадель', FALSE, '{55,35,5,5}', '{30,30,20,20}'),
+    ('Боевой маг', 'Цитадель', TRUE, '{30,20,25,25}', '{25,25,25,25}'),
+    ('Хозяин зверей', 'Крепость', FALSE, '{30,50,10,10}', '{30,30,20,20}'),
+    ('Ведьма', 'Крепость', TRUE, '{5,15,40,40}', '{20,20,30,30}'),
+    ('Путешественник', 'Сопряжение', FALSE, '{45,25,15,15}', '{30,30,20,20}'),
+    ('Элементалист', 'Сопряжение', TRUE, '{15,15,35,35}', '{25,25,25,25}')
+ON CONFLICT (name) DO UPDATE SET
+    town = EXCLUDED.town,
+    is_magic = EXCLUDED.is_magic,
+    low_level_growth = EXCLUDED.low_level_growth,
+    high_level_growth = EXCLUDED.high_level_growth;
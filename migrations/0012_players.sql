@@ -0,0 +1,21 @@
+CREATE TABLE players (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    -- 0xRRGGBB
+    color INTEGER NOT NULL CHECK (color BETWEEN 0 AND 16777215)
+);
+
+INSERT INTO players (name, color) VALUES
+    ('Красный', x'FF0000'::INTEGER),
+    ('Синий', x'3152FF'::INTEGER),
+    ('Коричневый', x'9C7352'::INTEGER),
+    ('Зелёный', x'429429'::INTEGER),
+    ('Оранжевый', x'FF8400'::INTEGER),
+    ('Фиолетовый', x'8C29A5'::INTEGER),
+    ('Бирюзовый', x'089CA5'::INTEGER),
+    ('Розовый', x'C67B8C'::INTEGER);
+
+-- Every hero so far belonged to the one and only player.
+ALTER TABLE heroes ADD COLUMN player_id INTEGER REFERENCES players (id);
+UPDATE heroes SET player_id = (SELECT MIN(id) FROM players);
+ALTER TABLE heroes ALTER COLUMN player_id SET NOT NULL;
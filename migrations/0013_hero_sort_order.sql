@@ -0,0 +1,2 @@
+ALTER TABLE heroes ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+UPDATE heroes SET sort_order = id;
@@ -0,0 +1,3 @@
+ALTER TABLE heroes
+    ADD COLUMN levels_without_wisdom SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN levels_without_magic_school SMALLINT NOT NULL DEFAULT 0;
@@ -0,0 +1,22 @@
+-- Rules used to find their skills by display name; give the kinds they need a
+-- stable code instead, so renaming a kind doesn't quietly switch a rule off.
+ALTER TABLE skill_kinds ADD COLUMN code TEXT UNIQUE;
+
+INSERT INTO skill_kinds (name, code) VALUES
+    ('Удача', 'luck'),
+    ('Лидерство', 'leadership'),
+    ('Интеллект', 'intelligence'),
+    ('Мудрость', 'wisdom'),
+    ('Логистика', 'logistics'),
+    ('Поиск пути', 'pathfinding'),
+    ('Разведка', 'scouting'),
+    ('Мистицизм', 'mysticism'),
+    ('Магия Воздуха', 'air_magic'),
+    ('Магия Земли', 'earth_magic'),
+    ('Магия Огня', 'fire_magic'),
+    ('Магия Воды', 'water_magic')
+ON CONFLICT (name) DO UPDATE SET code = EXCLUDED.code;
+
+-- The same for undead, which were told apart by the name of their town.
+ALTER TABLE creatures ADD COLUMN undead BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE creatures SET undead = TRUE WHERE town = 'Некрополис';
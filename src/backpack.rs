@@ -1,7 +1,118 @@
-#[derive(Clone, Debug)]
-pub struct Backpack {}
+use crate::stats::Bonuses;
+use crate::utils::RawImage;
 
-pub struct Item {}
+/// Worn artifacts and the unlimited list of carried ones.
+#[derive(Clone, Debug, Default)]
+pub struct Backpack {
+    /// Indexed by `ItemSlot as usize`.
+    pub equipped: [Option<Item>; 17],
+    pub items: Vec<Item>,
+}
+
+impl Backpack {
+    /// Puts `item` on, moving whatever was worn in `slot` to the backpack.
+    pub fn equip(&mut self, slot: ItemSlot, item: Item) -> Result<(), String> {
+        if !slot.accepts(&item) {
+            return Err(format!(
+                "{} нельзя надеть в слот «{}»",
+                item.name,
+                slot.kind().name()
+            ));
+        }
+        if let Some(old_item) = self.equipped[slot as usize].replace(item) {
+            self.items.push(old_item);
+        }
+        Ok(())
+    }
+
+    /// Puts on backpack item `idx`, taking the first free slot of its kind or
+    /// swapping it with the item in the first one.
+    pub fn equip_from_backpack(&mut self, idx: usize) {
+        let kind = self.items[idx].kind;
+        let mut slots = ItemSlot::ALL.into_iter().filter(|s| s.kind() == kind);
+        let free_slot = slots.clone().find(|s| self.equipped[*s as usize].is_none());
+        if let Some(slot) = free_slot.or_else(|| slots.next()) {
+            let item = self.items.remove(idx);
+            if let Some(old_item) = self.equipped[slot as usize].replace(item) {
+                self.items.insert(idx, old_item);
+            }
+        }
+    }
+
+    pub fn unequip(&mut self, slot: ItemSlot) {
+        if let Some(item) = self.equipped[slot as usize].take() {
+            self.items.push(item);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Item {
+    pub id: usize,
+    pub name: String,
+    pub kind: ItemKind,
+    pub image: RawImage,
+    /// Applied while the item is worn.
+    pub bonuses: Bonuses,
+}
+
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+/// What an item is worn as. Every `ItemSlot` takes exactly one kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    RightHand,
+    Helmet,
+    Amulet,
+    Armor,
+    Support,
+    LeftHand,
+    Cloak,
+    Misc,
+}
+
+impl Default for ItemKind {
+    fn default() -> Self {
+        ItemKind::Misc
+    }
+}
+
+impl ItemKind {
+    pub const ALL: [ItemKind; 8] = [
+        ItemKind::RightHand,
+        ItemKind::Helmet,
+        ItemKind::Amulet,
+        ItemKind::Armor,
+        ItemKind::Support,
+        ItemKind::LeftHand,
+        ItemKind::Cloak,
+        ItemKind::Misc,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::RightHand => "Правая рука",
+            ItemKind::Helmet => "Шлем",
+            ItemKind::Amulet => "Амулет",
+            ItemKind::Armor => "Доспех",
+            ItemKind::Support => "Поддержка",
+            ItemKind::LeftHand => "Левая рука",
+            ItemKind::Cloak => "Плащ",
+            ItemKind::Misc => "Разное",
+        }
+    }
+
+    /// Unknown values (e.g. from a newer schema) fall back to `Misc`.
+    pub fn from_index(idx: usize) -> ItemKind {
+        ItemKind::ALL.get(idx).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ItemSlot {
     RightHand1,
     RightHand2,
@@ -21,3 +132,156 @@ pub enum ItemSlot {
     Misc4,
     Misc5,
 }
+
+impl ItemSlot {
+    pub const ALL: [ItemSlot; 17] = [
+        ItemSlot::RightHand1,
+        ItemSlot::RightHand2,
+        ItemSlot::Helmet,
+        ItemSlot::Amulet,
+        ItemSlot::Armor,
+        ItemSlot::Support1,
+        ItemSlot::Support2,
+        ItemSlot::Support3,
+        ItemSlot::Support4,
+        ItemSlot::LeftHand1,
+        ItemSlot::LeftHand2,
+        ItemSlot::Cloak,
+        ItemSlot::Misc1,
+        ItemSlot::Misc2,
+        ItemSlot::Misc3,
+        ItemSlot::Misc4,
+        ItemSlot::Misc5,
+    ];
+
+    pub fn kind(self) -> ItemKind {
+        match self {
+            ItemSlot::RightHand1 | ItemSlot::RightHand2 => ItemKind::RightHand,
+            ItemSlot::Helmet => ItemKind::Helmet,
+            ItemSlot::Amulet => ItemKind::Amulet,
+            ItemSlot::Armor => ItemKind::Armor,
+            ItemSlot::Support1 | ItemSlot::Support2 | ItemSlot::Support3 | ItemSlot::Support4 => {
+                ItemKind::Support
+            }
+            ItemSlot::LeftHand1 | ItemSlot::LeftHand2 => ItemKind::LeftHand,
+            ItemSlot::Cloak => ItemKind::Cloak,
+            ItemSlot::Misc1
+            | ItemSlot::Misc2
+            | ItemSlot::Misc3
+            | ItemSlot::Misc4
+            | ItemSlot::Misc5 => ItemKind::Misc,
+        }
+    }
+
+    pub fn accepts(self, item: &Item) -> bool {
+        self.kind() == item.kind
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: usize, kind: ItemKind) -> Item {
+        Item {
+            id,
+            name: format!("Артефакт {}", id),
+            kind,
+            ..Item::default()
+        }
+    }
+
+    fn ids(items: &[Item]) -> Vec<usize> {
+        items.iter().map(|i| i.id).collect()
+    }
+
+    #[test]
+    fn equip_refuses_a_slot_of_another_kind() {
+        let mut backpack = Backpack::default();
+        let result = backpack.equip(ItemSlot::Helmet, item(1, ItemKind::Cloak));
+        assert_eq!(
+            result,
+            Err("Артефакт 1 нельзя надеть в слот «Шлем»".to_string())
+        );
+        assert!(backpack.equipped[ItemSlot::Helmet as usize].is_none());
+        assert!(backpack.items.is_empty());
+    }
+
+    #[test]
+    fn equip_moves_the_worn_item_to_the_backpack() {
+        let mut backpack = Backpack::default();
+        backpack
+            .equip(ItemSlot::Helmet, item(1, ItemKind::Helmet))
+            .unwrap();
+        backpack
+            .equip(ItemSlot::Helmet, item(2, ItemKind::Helmet))
+            .unwrap();
+        assert_eq!(
+            backpack.equipped[ItemSlot::Helmet as usize],
+            Some(item(2, ItemKind::Helmet))
+        );
+        assert_eq!(ids(&backpack.items), [1]);
+    }
+
+    #[test]
+    fn equip_from_backpack_takes_a_free_slot_first() {
+        let mut backpack = Backpack::default();
+        backpack
+            .equip(ItemSlot::RightHand1, item(1, ItemKind::RightHand))
+            .unwrap();
+        backpack.items = vec![item(2, ItemKind::Misc), item(3, ItemKind::RightHand)];
+        backpack.equip_from_backpack(1);
+        assert_eq!(
+            backpack.equipped[ItemSlot::RightHand2 as usize],
+            Some(item(3, ItemKind::RightHand))
+        );
+        assert_eq!(ids(&backpack.items), [2]);
+    }
+
+    #[test]
+    fn equip_from_backpack_swaps_when_every_slot_is_taken() {
+        let mut backpack = Backpack::default();
+        backpack
+            .equip(ItemSlot::Cloak, item(1, ItemKind::Cloak))
+            .unwrap();
+        backpack.items = vec![
+            item(2, ItemKind::Misc),
+            item(3, ItemKind::Cloak),
+            item(4, ItemKind::Misc),
+        ];
+        backpack.equip_from_backpack(1);
+        assert_eq!(
+            backpack.equipped[ItemSlot::Cloak as usize],
+            Some(item(3, ItemKind::Cloak))
+        );
+        assert_eq!(ids(&backpack.items), [2, 1, 4]);
+    }
+
+    #[test]
+    fn unequip_appends_to_the_backpack() {
+        let mut backpack = Backpack::default();
+        backpack
+            .equip(ItemSlot::Amulet, item(1, ItemKind::Amulet))
+            .unwrap();
+        backpack.unequip(ItemSlot::Amulet);
+        assert!(backpack.equipped[ItemSlot::Amulet as usize].is_none());
+        assert_eq!(ids(&backpack.items), [1]);
+
+        backpack
+            .items
+            .extend((2..10).map(|id| item(id, ItemKind::Misc)));
+        backpack
+            .equip(ItemSlot::Armor, item(10, ItemKind::Armor))
+            .unwrap();
+        backpack.unequip(ItemSlot::Armor);
+        assert_eq!(ids(&backpack.items), (1..=10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unequip_of_an_empty_slot_changes_nothing() {
+        let mut backpack = Backpack::default();
+        backpack.unequip(ItemSlot::Misc1);
+        assert!(backpack.items.is_empty());
+        assert!(backpack.equipped.iter().all(Option::is_none));
+    }
+}
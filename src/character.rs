@@ -1,6 +1,6 @@
 use crate::utils::RawImage;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Character {
     pub portrait: RawImage,
     pub portrait_small: RawImage,
@@ -0,0 +1,244 @@
+use sqlx::postgres::PgConnectOptions;
+
+/// Key under which the settings popup remembers the connection in `epi::Storage`.
+pub const DB_CONFIG_STORAGE_KEY: &str = "db_config";
+
+const CONFIG_FILE_ENV: &str = "HOMM3_DB_CONFIG";
+const DEFAULT_CONFIG_FILE: &str = "homm3_hero_viewer.conf";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+    /// Database to connect to while creating or dropping `database`.
+    pub maintenance_database: String,
+    pub pool_size: u32,
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 5432,
+            user: "homm3".to_string(),
+            password: "homm3".to_string(),
+            database: "homm3_heroes".to_string(),
+            maintenance_database: "homm3".to_string(),
+            pool_size: 5,
+        }
+    }
+}
+
+impl DbConfig {
+    /// Builds the config the viewer starts with. Later sources override
+    /// earlier ones:
+    /// 1. built-in defaults,
+    /// 2. whatever was last edited in the settings popup (`storage`),
+    /// 3. the config file from `--db-config <path>`, `$HOMM3_DB_CONFIG` or
+    ///    `./homm3_hero_viewer.conf`,
+    /// 4. `HOMM3_DB_HOST`, `HOMM3_DB_PORT`, ... environment variables,
+    /// 5. `--db-host <value>`, `--db-port <value>`, ... command line flags.
+    ///
+    /// Also returns a message for each setting that couldn't be applied.
+    pub fn load(stored: &str) -> (DbConfig, Vec<String>) {
+        let mut config = DbConfig::default();
+        let mut errors = config.apply_str(stored);
+
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        let file_from_args = args
+            .iter()
+            .position(|a| a == "--db-config")
+            .and_then(|i| args.get(i + 1).cloned());
+        let config_file = file_from_args
+            .or_else(|| std::env::var(CONFIG_FILE_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_CONFIG_FILE.to_string());
+        if let Ok(contents) = std::fs::read_to_string(&config_file) {
+            errors.extend(config.apply_str(&contents));
+        }
+
+        for key in KEYS {
+            let var = format!("HOMM3_DB_{}", key.to_uppercase());
+            if let Ok(value) = std::env::var(&var) {
+                errors.extend(config.set(key, &value).err());
+            }
+        }
+
+        for pair in args.windows(2) {
+            if let Some(key) = pair[0].strip_prefix("--db-") {
+                if key != "config" {
+                    errors.extend(config.set(&key.replace('-', "_"), &pair[1]).err());
+                }
+            }
+        }
+
+        (config, errors)
+    }
+
+    pub fn connect_options(&self) -> PgConnectOptions {
+        self.options_for(&self.database)
+    }
+
+    pub fn maintenance_connect_options(&self) -> PgConnectOptions {
+        self.options_for(&self.maintenance_database)
+    }
+
+    fn options_for(&self, database: &str) -> PgConnectOptions {
+        PgConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .username(&self.user)
+            .password(&self.password)
+            .database(database)
+    }
+
+    /// What the settings popup remembers in storage, as `key = value` lines:
+    /// the `stored` settings with every field edited since the config was
+    /// `loaded`. Values from the file, the environment or the command line
+    /// aren't copied in, and the password is never kept.
+    pub fn storage_string(&self, loaded: &DbConfig, stored: &str) -> String {
+        let mut remembered = DbConfig::default();
+        // Bad lines were reported when the config was loaded.
+        let _ = remembered.apply_str(stored);
+        KEYS.iter()
+            .filter(|key| **key != "password")
+            .map(|key| {
+                let value = if self.get(key) != loaded.get(key) {
+                    self.get(key)
+                } else {
+                    remembered.get(key)
+                };
+                format!("{} = {}\n", key, value)
+            })
+            .collect()
+    }
+
+    /// Applies every `key = value` line of `contents`, skipping blank lines and
+    /// `#` comments. Returns a message for each line that couldn't be applied.
+    pub fn apply_str(&mut self, contents: &str) -> Vec<String> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|line| match line.split_once('=') {
+                Some((key, value)) => self.set(key.trim(), value.trim()).err(),
+                None => Some(format!("Не удалось разобрать строку настроек: {}", line)),
+            })
+            .collect()
+    }
+
+    fn get(&self, key: &str) -> String {
+        match key {
+            "host" => self.host.clone(),
+            "port" => self.port.to_string(),
+            "user" => self.user.clone(),
+            "password" => self.password.clone(),
+            "database" => self.database.clone(),
+            "maintenance_database" => self.maintenance_database.clone(),
+            "pool_size" => self.pool_size.to_string(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = |_| format!("Неверное значение {} для {}", value, key);
+        match key {
+            "host" => self.host = value.to_string(),
+            "port" => self.port = value.parse().map_err(invalid)?,
+            "user" => self.user = value.to_string(),
+            "password" => self.password = value.to_string(),
+            "database" => self.database = value.to_string(),
+            "maintenance_database" => self.maintenance_database = value.to_string(),
+            "pool_size" => self.pool_size = value.parse().map_err(invalid)?,
+            _ => return Err(format!("Неизвестный параметр {}", key)),
+        }
+        Ok(())
+    }
+}
+
+const KEYS: [&str; 7] = [
+    "host",
+    "port",
+    "user",
+    "password",
+    "database",
+    "maintenance_database",
+    "pool_size",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_str_skips_comments_and_blank_lines() {
+        let mut config = DbConfig::default();
+        let errors = config.apply_str("# local\n\nhost = db.local\n  port=6543  \n");
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(config.host, "db.local");
+        assert_eq!(config.port, 6543);
+        assert_eq!(config.user, DbConfig::default().user);
+    }
+
+    #[test]
+    fn apply_str_reports_bad_lines_and_keeps_going() {
+        let mut config = DbConfig::default();
+        let errors = config.apply_str("port = x\nfoo = 1\ngarbage\npool_size = 9");
+        assert_eq!(
+            errors,
+            [
+                "Неверное значение x для port",
+                "Неизвестный параметр foo",
+                "Не удалось разобрать строку настроек: garbage",
+            ]
+        );
+        assert_eq!(config.port, 5432);
+        assert_eq!(config.pool_size, 9);
+    }
+
+    #[test]
+    fn storage_string_reads_back_without_the_password() {
+        let config = DbConfig {
+            host: "example.org".to_string(),
+            password: "secret".to_string(),
+            pool_size: 2,
+            ..DbConfig::default()
+        };
+        let stored = config.storage_string(&DbConfig::default(), "");
+        assert!(!stored.contains("secret"));
+
+        let mut read_back = DbConfig::default();
+        assert!(read_back.apply_str(&stored).is_empty());
+        assert_eq!(
+            read_back,
+            DbConfig {
+                password: DbConfig::default().password,
+                ..config
+            }
+        );
+    }
+
+    #[test]
+    fn storage_string_keeps_only_edits() {
+        let stored = "user = admin\npassword = old\n";
+        // Loaded with the host from the environment, then the port edited.
+        let loaded = DbConfig {
+            host: "from-env".to_string(),
+            user: "admin".to_string(),
+            ..DbConfig::default()
+        };
+        let edited = DbConfig {
+            port: 6000,
+            ..loaded.clone()
+        };
+
+        let mut read_back = DbConfig::default();
+        read_back.apply_str(&edited.storage_string(&loaded, stored));
+        assert_eq!(read_back.host, DbConfig::default().host);
+        assert_eq!(read_back.port, 6000);
+        assert_eq!(read_back.user, "admin");
+        assert_eq!(read_back.password, DbConfig::default().password);
+    }
+}
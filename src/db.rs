@@ -0,0 +1,1094 @@
+use std::collections::HashMap;
+
+use sqlx::postgres::PgPool;
+
+use crate::backpack::{Backpack, Item, ItemKind};
+use crate::character::Character;
+use crate::experience::level_for_experience;
+use crate::hero::Hero;
+use crate::hero_class::{ClassKind, HeroClass};
+use crate::player::Player;
+use crate::search::{SearchKey, SearchReply, PAGE_SIZE};
+use crate::skill::{Mastery, Skill, SkillKind};
+use crate::skill_offers::OfferStreaks;
+use crate::spec::Spec;
+use crate::spell::{Spell, SpellSchool};
+use crate::unit::{Creature, Unit};
+use crate::utils::Textures;
+
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
+
+#[derive(sqlx::FromRow)]
+pub struct PlayerRow {
+    pub id: i32,
+    pub name: String,
+    pub color: i32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct ClassRow {
+    pub id: i32,
+    pub name: String,
+    pub town: String,
+    pub is_magic: bool,
+    pub low_level_growth: Vec<i16>,
+    pub high_level_growth: Vec<i16>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct ClassSkillWeightRow {
+    pub class_id: i32,
+    pub skill_kind_id: i32,
+    pub name: String,
+    pub code: Option<String>,
+    pub weight: i16,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct SkillKindRow {
+    pub id: i32,
+    pub name: String,
+    pub code: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct SkillRow {
+    pub id: i32,
+    pub kind_id: i32,
+    pub name: String,
+    pub code: Option<String>,
+    pub level: i16,
+    pub image: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct SpecRow {
+    pub id: i32,
+    pub name: String,
+    pub class: String,
+    pub image: Vec<u8>,
+    pub attack: i16,
+    pub defence: i16,
+    pub power: i16,
+    pub knowledge: i16,
+    pub luck: i16,
+    pub morale: i16,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct HeroRow {
+    pub id: i32,
+    pub player_id: i32,
+    pub sort_order: i32,
+    pub name: String,
+    pub class: String,
+    pub portrait: Vec<u8>,
+    pub portrait_small: Vec<u8>,
+    pub spec_id: Option<i32>,
+    pub attack: i16,
+    pub defence: i16,
+    pub power: i16,
+    pub knowledge: i16,
+    pub luck: i16,
+    pub morale: i16,
+    pub experience: i32,
+    pub mana_max: i32,
+    pub mana_max_manual: bool,
+    pub mana_current: i32,
+    pub level: i16,
+    pub levels_without_wisdom: i16,
+    pub levels_without_magic_school: i16,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct HeroSkillRow {
+    pub hero_id: i32,
+    pub slot: i16,
+    pub skill_id: i32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct CreatureRow {
+    pub id: i32,
+    pub name: String,
+    pub town: String,
+    pub speed: i16,
+    pub undead: bool,
+    pub portrait: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct HeroUnitRow {
+    pub hero_id: i32,
+    pub slot: i16,
+    pub creature_id: i32,
+    pub count: i32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct ItemRow {
+    pub id: i32,
+    pub name: String,
+    pub kind: i16,
+    pub image: Vec<u8>,
+    pub attack: i16,
+    pub defence: i16,
+    pub power: i16,
+    pub knowledge: i16,
+    pub luck: i16,
+    pub morale: i16,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct HeroEquipmentRow {
+    pub hero_id: i32,
+    pub slot: i16,
+    pub item_id: i32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct HeroBackpackRow {
+    pub hero_id: i32,
+    pub item_id: i32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct SpellRow {
+    pub id: i32,
+    pub name: String,
+    pub school: i16,
+    pub level: i16,
+    pub cost: i16,
+    pub power_factor: i16,
+    pub image: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct HeroSpellRow {
+    pub hero_id: i32,
+    pub spell_id: i32,
+}
+
+/// Everything the viewer shows, fetched in one go on a worker thread.
+/// Textures can only be allocated with a frame, so rows keep raw image bytes
+/// until [`Snapshot::into_models`] is called from `update`.
+pub struct Snapshot {
+    pub players: Vec<PlayerRow>,
+    pub classes: Vec<ClassRow>,
+    pub class_skill_weights: Vec<ClassSkillWeightRow>,
+    pub skill_kinds: Vec<SkillKindRow>,
+    pub skills: Vec<SkillRow>,
+    pub specs: Vec<SpecRow>,
+    pub heroes: Vec<HeroRow>,
+    pub hero_skills: Vec<HeroSkillRow>,
+    pub creatures: Vec<CreatureRow>,
+    pub hero_units: Vec<HeroUnitRow>,
+    pub items: Vec<ItemRow>,
+    pub hero_equipment: Vec<HeroEquipmentRow>,
+    /// Ordered by position within each hero's backpack.
+    pub hero_backpack: Vec<HeroBackpackRow>,
+    pub spells: Vec<SpellRow>,
+    pub hero_spells: Vec<HeroSpellRow>,
+}
+
+pub struct Models {
+    pub players: Vec<Player>,
+    pub classes: Vec<HeroClass>,
+    pub skill_kinds: Vec<SkillKind>,
+    /// Every mastery of the skill kinds the heroes hold.
+    pub skills: Vec<Skill>,
+    pub heroes: Vec<Hero>,
+}
+
+/// Growth chances are stored as a 4-element array.
+fn growth_from_row(row: &[i16]) -> [u8; 4] {
+    let mut growth = [0; 4];
+    for (g, r) in growth.iter_mut().zip(row) {
+        *g = *r as u8;
+    }
+    growth
+}
+
+impl PlayerRow {
+    pub fn into_player(self) -> Player {
+        let [_, r, g, b] = self.color.to_be_bytes();
+        Player {
+            id: self.id as usize,
+            name: self.name,
+            color: [r, g, b],
+        }
+    }
+}
+
+impl ClassRow {
+    pub fn into_class(self, skill_weights: Vec<(SkillKind, u32)>) -> HeroClass {
+        HeroClass {
+            id: self.id as usize,
+            name: self.name,
+            town: self.town,
+            kind: if self.is_magic {
+                ClassKind::Magic
+            } else {
+                ClassKind::Might
+            },
+            low_level_growth: growth_from_row(&self.low_level_growth),
+            high_level_growth: growth_from_row(&self.high_level_growth),
+            skill_weights,
+        }
+    }
+}
+
+impl SkillRow {
+    pub fn into_skill(self, textures: &mut Textures, frame: &mut eframe::epi::Frame<'_>) -> Skill {
+        Skill {
+            id: self.id as usize,
+            kind: SkillKind {
+                id: self.kind_id as usize,
+                name: self.name,
+                code: self.code,
+            },
+            mastery: Mastery::from_level(self.level as u8),
+            image: textures.load(&self.image, frame),
+        }
+    }
+}
+
+impl SpecRow {
+    pub fn into_spec(self, textures: &mut Textures, frame: &mut eframe::epi::Frame<'_>) -> Spec {
+        Spec {
+            id: self.id as usize,
+            name: self.name,
+            class: self.class,
+            image: textures.load(&self.image, frame),
+            bonuses: [
+                self.attack as i8,
+                self.defence as i8,
+                self.power as i8,
+                self.knowledge as i8,
+                self.luck as i8,
+                self.morale as i8,
+            ],
+        }
+    }
+}
+
+impl CreatureRow {
+    pub fn into_creature(
+        self,
+        textures: &mut Textures,
+        frame: &mut eframe::epi::Frame<'_>,
+    ) -> Creature {
+        Creature {
+            id: self.id as usize,
+            name: self.name,
+            town: self.town,
+            speed: self.speed as u8,
+            undead: self.undead,
+            portrait: textures.load(&self.portrait, frame),
+        }
+    }
+}
+
+impl ItemRow {
+    pub fn into_item(self, textures: &mut Textures, frame: &mut eframe::epi::Frame<'_>) -> Item {
+        Item {
+            id: self.id as usize,
+            name: self.name,
+            kind: ItemKind::from_index(self.kind as usize),
+            image: textures.load(&self.image, frame),
+            bonuses: [
+                self.attack as i8,
+                self.defence as i8,
+                self.power as i8,
+                self.knowledge as i8,
+                self.luck as i8,
+                self.morale as i8,
+            ],
+        }
+    }
+}
+
+impl SpellRow {
+    pub fn into_spell(self, textures: &mut Textures, frame: &mut eframe::epi::Frame<'_>) -> Spell {
+        Spell {
+            id: self.id as usize,
+            name: self.name,
+            school: SpellSchool::from_index(self.school as usize),
+            level: self.level as u8,
+            cost: self.cost as u16,
+            power_factor: self.power_factor as u16,
+            image: textures.load(&self.image, frame),
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn into_models(
+        self,
+        textures: &mut Textures,
+        frame: &mut eframe::epi::Frame<'_>,
+    ) -> Models {
+        let mut skill_weights: HashMap<i32, Vec<(SkillKind, u32)>> = HashMap::new();
+        for row in self.class_skill_weights {
+            let weights = skill_weights.entry(row.class_id).or_default();
+            let kind = SkillKind {
+                id: row.skill_kind_id as usize,
+                name: row.name,
+                code: row.code,
+            };
+            weights.push((kind, row.weight as u32));
+        }
+        let classes = self
+            .classes
+            .into_iter()
+            .map(|r| {
+                let weights = skill_weights.remove(&r.id).unwrap_or_default();
+                r.into_class(weights)
+            })
+            .collect();
+
+        let skills_by_id = self
+            .skills
+            .into_iter()
+            .map(|r| (r.id, r.into_skill(textures, frame)))
+            .collect::<HashMap<_, _>>();
+        let specs_by_id = self
+            .specs
+            .into_iter()
+            .map(|r| (r.id, r.into_spec(textures, frame)))
+            .collect::<HashMap<_, _>>();
+
+        let mut hero_skills: HashMap<i32, [Option<Skill>; 8]> = HashMap::new();
+        for row in self.hero_skills {
+            let slots = hero_skills.entry(row.hero_id).or_default();
+            slots[row.slot as usize] = skills_by_id.get(&row.skill_id).cloned();
+        }
+
+        let creatures_by_id = self
+            .creatures
+            .into_iter()
+            .map(|r| (r.id, r.into_creature(textures, frame)))
+            .collect::<HashMap<_, _>>();
+        let mut hero_units: HashMap<i32, [Option<Unit>; 7]> = HashMap::new();
+        for row in self.hero_units {
+            let slots = hero_units.entry(row.hero_id).or_default();
+            slots[row.slot as usize] = creatures_by_id.get(&row.creature_id).map(|c| Unit {
+                creature: c.clone(),
+                count: row.count as u32,
+            });
+        }
+
+        let items_by_id = self
+            .items
+            .into_iter()
+            .map(|r| (r.id, r.into_item(textures, frame)))
+            .collect::<HashMap<_, _>>();
+        let mut backpacks: HashMap<i32, Backpack> = HashMap::new();
+        for row in self.hero_equipment {
+            let backpack = backpacks.entry(row.hero_id).or_default();
+            backpack.equipped[row.slot as usize] = items_by_id.get(&row.item_id).cloned();
+        }
+        for row in self.hero_backpack {
+            let backpack = backpacks.entry(row.hero_id).or_default();
+            backpack
+                .items
+                .extend(items_by_id.get(&row.item_id).cloned());
+        }
+
+        let spells_by_id = self
+            .spells
+            .into_iter()
+            .map(|r| (r.id, r.into_spell(textures, frame)))
+            .collect::<HashMap<_, _>>();
+        let mut spellbooks: HashMap<i32, Vec<Spell>> = HashMap::new();
+        for row in self.hero_spells {
+            let spellbook = spellbooks.entry(row.hero_id).or_default();
+            spellbook.extend(spells_by_id.get(&row.spell_id).cloned());
+        }
+
+        let heroes = self
+            .heroes
+            .into_iter()
+            .map(|r| Hero {
+                id: r.id as usize,
+                player_id: r.player_id as usize,
+                sort_order: r.sort_order as usize,
+                character: Character {
+                    portrait: textures.load(&r.portrait, frame),
+                    portrait_small: textures.load(&r.portrait_small, frame),
+                    name: r.name,
+                    class: r.class,
+                },
+                units: hero_units.remove(&r.id).unwrap_or_default(),
+                skills: hero_skills.remove(&r.id).unwrap_or_default(),
+                backpack: backpacks.remove(&r.id).unwrap_or_default(),
+                spellbook: spellbooks.remove(&r.id).unwrap_or_default(),
+                pskills: [
+                    r.attack as u8,
+                    r.defence as u8,
+                    r.power as u8,
+                    r.knowledge as u8,
+                ],
+                spec: r
+                    .spec_id
+                    .and_then(|id| specs_by_id.get(&id).cloned())
+                    .unwrap_or_default(),
+                luck: r.luck as i8,
+                morale: r.morale as i8,
+                experience: r.experience as u32,
+                mana_max: r.mana_max as u16,
+                mana_max_manual: r.mana_max_manual,
+                mana_current: r.mana_current as u16,
+                level: level_for_experience(r.experience as u32),
+                offer_streaks: OfferStreaks {
+                    without_wisdom: r.levels_without_wisdom as u8,
+                    without_magic_school: r.levels_without_magic_school as u8,
+                },
+            })
+            .collect();
+
+        Models {
+            players: self
+                .players
+                .into_iter()
+                .map(PlayerRow::into_player)
+                .collect(),
+            classes,
+            skill_kinds: self
+                .skill_kinds
+                .into_iter()
+                .map(|r| SkillKind {
+                    id: r.id as usize,
+                    name: r.name,
+                    code: r.code,
+                })
+                .collect(),
+            skills: skills_by_id.into_values().collect(),
+            heroes,
+        }
+    }
+}
+
+pub async fn load_snapshot(pool: &PgPool) -> Result<Snapshot, sqlx::Error> {
+    let players = sqlx::query_as("SELECT id, name, color FROM players ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+    let classes = sqlx::query_as(
+        "SELECT id, name, town, is_magic, low_level_growth, high_level_growth
+         FROM classes ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+    let class_skill_weights = sqlx::query_as(
+        "SELECT w.class_id, w.skill_kind_id, k.name, k.code, w.weight
+         FROM class_skill_weights w JOIN skill_kinds k ON k.id = w.skill_kind_id
+         ORDER BY w.class_id, k.name",
+    )
+    .fetch_all(pool)
+    .await?;
+    let skill_kinds = sqlx::query_as("SELECT id, name, code FROM skill_kinds ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+    let skills = sqlx::query_as(
+        "SELECT s.id, s.kind_id, k.name, k.code, s.level, s.image
+         FROM skills s JOIN skill_kinds k ON k.id = s.kind_id
+         WHERE s.kind_id IN (SELECT kind_id FROM hero_skills)",
+    )
+    .fetch_all(pool)
+    .await?;
+    let specs = sqlx::query_as(
+        "SELECT s.id, s.name, c.name AS class, s.image,
+                s.attack, s.defence, s.power, s.knowledge, s.luck, s.morale
+         FROM specialties s JOIN classes c ON c.id = s.class_id
+         WHERE s.id IN (SELECT spec_id FROM heroes)",
+    )
+    .fetch_all(pool)
+    .await?;
+    let heroes = sqlx::query_as(
+        "SELECT h.id, h.player_id, h.sort_order, ch.name, cl.name AS class, ch.portrait, ch.portrait_small,
+                h.spec_id, h.attack, h.defence, h.power, h.knowledge, h.luck, h.morale,
+                h.experience, h.mana_max, h.mana_max_manual, h.mana_current, h.level,
+                h.levels_without_wisdom, h.levels_without_magic_school
+         FROM heroes h
+         JOIN characters ch ON ch.id = h.character_id
+         JOIN classes cl ON cl.id = ch.class_id
+         ORDER BY h.id",
+    )
+    .fetch_all(pool)
+    .await?;
+    let hero_skills = sqlx::query_as("SELECT hero_id, slot, skill_id FROM hero_skills")
+        .fetch_all(pool)
+        .await?;
+    let creatures = sqlx::query_as(
+        "SELECT id, name, town, speed, undead, portrait FROM creatures
+         WHERE id IN (SELECT creature_id FROM hero_units)",
+    )
+    .fetch_all(pool)
+    .await?;
+    let hero_units = sqlx::query_as("SELECT hero_id, slot, creature_id, count FROM hero_units")
+        .fetch_all(pool)
+        .await?;
+    let items = sqlx::query_as(
+        "SELECT id, name, kind, image, attack, defence, power, knowledge, luck, morale
+         FROM items
+         WHERE id IN (SELECT item_id FROM hero_equipment)
+            OR id IN (SELECT item_id FROM hero_backpack)",
+    )
+    .fetch_all(pool)
+    .await?;
+    let hero_equipment = sqlx::query_as("SELECT hero_id, slot, item_id FROM hero_equipment")
+        .fetch_all(pool)
+        .await?;
+    let hero_backpack =
+        sqlx::query_as("SELECT hero_id, item_id FROM hero_backpack ORDER BY hero_id, position")
+            .fetch_all(pool)
+            .await?;
+    let spells = sqlx::query_as(
+        "SELECT id, name, school, level, cost, power_factor, image FROM spells
+         WHERE id IN (SELECT spell_id FROM hero_spells)",
+    )
+    .fetch_all(pool)
+    .await?;
+    let hero_spells = sqlx::query_as(
+        "SELECT hs.hero_id, hs.spell_id FROM hero_spells hs
+         JOIN spells s ON s.id = hs.spell_id
+         ORDER BY hs.hero_id, s.school, s.level, s.name",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Snapshot {
+        players,
+        classes,
+        class_skill_weights,
+        skill_kinds,
+        skills,
+        specs,
+        heroes,
+        hero_skills,
+        creatures,
+        hero_units,
+        items,
+        hero_equipment,
+        hero_backpack,
+        spells,
+        hero_spells,
+    })
+}
+
+/// Quotes a database name so it can be spliced into `CREATE/DROP DATABASE`,
+/// which don't accept bind parameters.
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `ILIKE` pattern matching `query` anywhere in the column, taken literally.
+fn contains_pattern(query: &str) -> String {
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+pub async fn count_skills(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+) -> Result<SearchReply<SkillRow>, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM skills s JOIN skill_kinds k ON k.id = s.kind_id
+         WHERE k.name ILIKE $1",
+    )
+    .bind(contains_pattern(&key.1))
+    .fetch_one(pool)
+    .await?;
+    Ok(SearchReply::Count(generation, key, count as usize))
+}
+
+pub async fn skills_page(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+    page: usize,
+) -> Result<SearchReply<SkillRow>, sqlx::Error> {
+    let rows = sqlx::query_as(
+        "SELECT s.id, s.kind_id, k.name, k.code, s.level, s.image
+         FROM skills s JOIN skill_kinds k ON k.id = s.kind_id
+         WHERE k.name ILIKE $1
+         ORDER BY k.name, s.level, s.id
+         LIMIT $2 OFFSET $3",
+    )
+    .bind(contains_pattern(&key.1))
+    .bind(PAGE_SIZE as i64)
+    .bind((page * PAGE_SIZE) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(SearchReply::Page(generation, key, page, rows))
+}
+
+/// Kind ids and mastery levels of the skills a hero holds, written into the
+/// scope of a skill offer search as comma-separated `kind_id:level` pairs.
+fn held_skills(scope: &str) -> (Vec<i32>, Vec<i16>) {
+    scope
+        .split(',')
+        .filter_map(|pair| {
+            let (kind_id, level) = pair.split_once(':')?;
+            Some((kind_id.parse().ok()?, level.parse().ok()?))
+        })
+        .unzip()
+}
+
+/// Level-up candidates for the hero whose skills are in the scope of `key`:
+/// the kinds it lacks at basic mastery and the next mastery of the ones it has.
+pub async fn count_skill_offers(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+) -> Result<SearchReply<SkillRow>, sqlx::Error> {
+    let (kind_ids, levels) = held_skills(&key.0);
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM skills s
+         LEFT JOIN UNNEST($1::INTEGER[], $2::SMALLINT[]) AS held (kind_id, level)
+             ON held.kind_id = s.kind_id
+         WHERE (held.kind_id IS NULL AND s.level = 1) OR s.level = held.level + 1",
+    )
+    .bind(kind_ids)
+    .bind(levels)
+    .fetch_one(pool)
+    .await?;
+    Ok(SearchReply::Count(generation, key, count as usize))
+}
+
+pub async fn skill_offers_page(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+    page: usize,
+) -> Result<SearchReply<SkillRow>, sqlx::Error> {
+    let (kind_ids, levels) = held_skills(&key.0);
+    let rows = sqlx::query_as(
+        "SELECT s.id, s.kind_id, k.name, k.code, s.level, s.image
+         FROM skills s JOIN skill_kinds k ON k.id = s.kind_id
+         LEFT JOIN UNNEST($1::INTEGER[], $2::SMALLINT[]) AS held (kind_id, level)
+             ON held.kind_id = s.kind_id
+         WHERE (held.kind_id IS NULL AND s.level = 1) OR s.level = held.level + 1
+         ORDER BY k.name, s.level, s.id
+         LIMIT $3 OFFSET $4",
+    )
+    .bind(kind_ids)
+    .bind(levels)
+    .bind(PAGE_SIZE as i64)
+    .bind((page * PAGE_SIZE) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(SearchReply::Page(generation, key, page, rows))
+}
+
+/// Specialties are searched within a class, passed as the scope of `key`.
+pub async fn count_specs(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+) -> Result<SearchReply<SpecRow>, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM specialties s JOIN classes c ON c.id = s.class_id
+         WHERE c.name = $1 AND s.name ILIKE $2",
+    )
+    .bind(key.0.clone())
+    .bind(contains_pattern(&key.1))
+    .fetch_one(pool)
+    .await?;
+    Ok(SearchReply::Count(generation, key, count as usize))
+}
+
+pub async fn specs_page(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+    page: usize,
+) -> Result<SearchReply<SpecRow>, sqlx::Error> {
+    let rows = sqlx::query_as(
+        "SELECT s.id, s.name, c.name AS class, s.image,
+                s.attack, s.defence, s.power, s.knowledge, s.luck, s.morale
+         FROM specialties s JOIN classes c ON c.id = s.class_id
+         WHERE c.name = $1 AND s.name ILIKE $2
+         ORDER BY s.name, s.id
+         LIMIT $3 OFFSET $4",
+    )
+    .bind(key.0.clone())
+    .bind(contains_pattern(&key.1))
+    .bind(PAGE_SIZE as i64)
+    .bind((page * PAGE_SIZE) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(SearchReply::Page(generation, key, page, rows))
+}
+
+pub async fn count_creatures(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+) -> Result<SearchReply<CreatureRow>, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM creatures WHERE name ILIKE $1")
+        .bind(contains_pattern(&key.1))
+        .fetch_one(pool)
+        .await?;
+    Ok(SearchReply::Count(generation, key, count as usize))
+}
+
+pub async fn creatures_page(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+    page: usize,
+) -> Result<SearchReply<CreatureRow>, sqlx::Error> {
+    let rows = sqlx::query_as(
+        "SELECT id, name, town, speed, undead, portrait FROM creatures
+         WHERE name ILIKE $1
+         ORDER BY town, name, id
+         LIMIT $2 OFFSET $3",
+    )
+    .bind(contains_pattern(&key.1))
+    .bind(PAGE_SIZE as i64)
+    .bind((page * PAGE_SIZE) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(SearchReply::Page(generation, key, page, rows))
+}
+
+/// Items are searched within a kind, passed as its index in the scope of `key`;
+/// an empty scope searches all of them.
+pub async fn count_items(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+) -> Result<SearchReply<ItemRow>, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM items
+         WHERE ($1::SMALLINT IS NULL OR kind = $1) AND name ILIKE $2",
+    )
+    .bind(key.0.parse::<i16>().ok())
+    .bind(contains_pattern(&key.1))
+    .fetch_one(pool)
+    .await?;
+    Ok(SearchReply::Count(generation, key, count as usize))
+}
+
+pub async fn items_page(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+    page: usize,
+) -> Result<SearchReply<ItemRow>, sqlx::Error> {
+    let rows = sqlx::query_as(
+        "SELECT id, name, kind, image, attack, defence, power, knowledge, luck, morale
+         FROM items
+         WHERE ($1::SMALLINT IS NULL OR kind = $1) AND name ILIKE $2
+         ORDER BY kind, name, id
+         LIMIT $3 OFFSET $4",
+    )
+    .bind(key.0.parse::<i16>().ok())
+    .bind(contains_pattern(&key.1))
+    .bind(PAGE_SIZE as i64)
+    .bind((page * PAGE_SIZE) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(SearchReply::Page(generation, key, page, rows))
+}
+
+/// Spells are searched within a school like items within a kind, see
+/// [`count_items`].
+pub async fn count_spells(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+) -> Result<SearchReply<SpellRow>, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM spells
+         WHERE ($1::SMALLINT IS NULL OR school = $1) AND name ILIKE $2",
+    )
+    .bind(key.0.parse::<i16>().ok())
+    .bind(contains_pattern(&key.1))
+    .fetch_one(pool)
+    .await?;
+    Ok(SearchReply::Count(generation, key, count as usize))
+}
+
+pub async fn spells_page(
+    pool: &PgPool,
+    generation: u64,
+    key: SearchKey,
+    page: usize,
+) -> Result<SearchReply<SpellRow>, sqlx::Error> {
+    let rows = sqlx::query_as(
+        "SELECT id, name, school, level, cost, power_factor, image
+         FROM spells
+         WHERE ($1::SMALLINT IS NULL OR school = $1) AND name ILIKE $2
+         ORDER BY school, level, name, id
+         LIMIT $3 OFFSET $4",
+    )
+    .bind(key.0.parse::<i16>().ok())
+    .bind(contains_pattern(&key.1))
+    .bind(PAGE_SIZE as i64)
+    .bind((page * PAGE_SIZE) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(SearchReply::Page(generation, key, page, rows))
+}
+
+/// Fills a freshly migrated database with the two demo heroes and their
+/// armies, both owned by the first player. Their classes come with the
+/// migrations.
+pub async fn seed_demo_data(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let skills: [(&str, i16, &[u8]); 6] = [
+        (
+            "Некромантия",
+            3,
+            include_bytes!("../resources/skill/necro_3.png"),
+        ),
+        (
+            "Торговля",
+            1,
+            include_bytes!("../resources/skill/merchant_1.png"),
+        ),
+        (
+            "Нападение",
+            1,
+            include_bytes!("../resources/skill/offence_1.png"),
+        ),
+        ("Удача", 1, include_bytes!("../resources/skill/luck_1.png")),
+        ("Удача", 2, include_bytes!("../resources/skill/luck_2.png")),
+        ("Удача", 3, include_bytes!("../resources/skill/luck_3.png")),
+    ];
+    let mut skill_ids = Vec::new();
+    for (name, level, image) in skills {
+        let id: i32 = sqlx::query_scalar(
+            "WITH kind AS (
+                 INSERT INTO skill_kinds (name) VALUES ($1)
+                 ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+                 RETURNING id
+             )
+             INSERT INTO skills (kind_id, level, image) SELECT id, $2, $3 FROM kind
+             RETURNING id",
+        )
+        .bind(name)
+        .bind(level)
+        .bind(image)
+        .fetch_one(&mut tx)
+        .await?;
+        skill_ids.push(id);
+    }
+
+    let specs: [(&str, &str, &[u8]); 4] = [
+        (
+            "Ускорение",
+            "Путешественник",
+            include_bytes!("../resources/spec_speed.png"),
+        ),
+        (
+            "Волшебство",
+            "Путешественник",
+            include_bytes!("../resources/spec_wizard.png"),
+        ),
+        (
+            "Элементали",
+            "Путешественник",
+            include_bytes!("../resources/spec_psych.png"),
+        ),
+        (
+            "Джинны",
+            "Алхимик",
+            include_bytes!("../resources/spec_djini.png"),
+        ),
+    ];
+    let mut spec_ids = Vec::new();
+    for (name, class, image) in specs {
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO specialties (name, class_id, image)
+             VALUES ($1, (SELECT id FROM classes WHERE name = $2), $3)
+             RETURNING id",
+        )
+        .bind(name)
+        .bind(class)
+        .bind(image)
+        .fetch_one(&mut tx)
+        .await?;
+        spec_ids.push(id);
+    }
+
+    struct DemoHero {
+        name: &'static str,
+        class: &'static str,
+        portrait: &'static [u8],
+        portrait_small: &'static [u8],
+        spec_id: i32,
+        pskills: [i16; 4],
+        luck: i16,
+        morale: i16,
+        experience: i32,
+        mana_max: i32,
+        mana_max_manual: bool,
+        mana_current: i32,
+        level: i16,
+    }
+
+    let heroes = [
+        DemoHero {
+            name: "Монер",
+            class: "Путешественник",
+            portrait: include_bytes!("../resources/hpl004pl.png"),
+            portrait_small: include_bytes!("../resources/hps004pl.png"),
+            spec_id: spec_ids[0],
+            pskills: [10, 4, 12, 5],
+            luck: 3,
+            morale: -2,
+            experience: 48279,
+            mana_max: 334,
+            mana_max_manual: true,
+            mana_current: 210,
+            level: 17,
+        },
+        DemoHero {
+            name: "Тан",
+            class: "Алхимик",
+            portrait: include_bytes!("../resources/hpl033al.png"),
+            portrait_small: include_bytes!("../resources/hps033al.png"),
+            spec_id: spec_ids[3],
+            pskills: [1, 1, 2, 2],
+            luck: -2,
+            morale: 2,
+            experience: 0,
+            mana_max: 10,
+            mana_max_manual: false,
+            mana_current: 10,
+            level: 1,
+        },
+    ];
+    let mut hero_ids = Vec::new();
+    for hero in heroes {
+        let character_id: i32 = sqlx::query_scalar(
+            "INSERT INTO characters (name, class_id, portrait, portrait_small)
+             VALUES ($1, (SELECT id FROM classes WHERE name = $2), $3, $4)
+             RETURNING id",
+        )
+        .bind(hero.name)
+        .bind(hero.class)
+        .bind(hero.portrait)
+        .bind(hero.portrait_small)
+        .fetch_one(&mut tx)
+        .await?;
+
+        let hero_id: i32 = sqlx::query_scalar(
+            "INSERT INTO heroes (character_id, spec_id, attack, defence, power, knowledge,
+                                 luck, morale, experience, mana_max, mana_max_manual,
+                                 mana_current, level, player_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
+                     (SELECT MIN(id) FROM players))
+             RETURNING id",
+        )
+        .bind(character_id)
+        .bind(hero.spec_id)
+        .bind(hero.pskills[0])
+        .bind(hero.pskills[1])
+        .bind(hero.pskills[2])
+        .bind(hero.pskills[3])
+        .bind(hero.luck)
+        .bind(hero.morale)
+        .bind(hero.experience)
+        .bind(hero.mana_max)
+        .bind(hero.mana_max_manual)
+        .bind(hero.mana_current)
+        .bind(hero.level)
+        .fetch_one(&mut tx)
+        .await?;
+        hero_ids.push(hero_id);
+    }
+
+    // No portraits ship for creatures; they can be picked in the editor.
+    let creatures: [(&str, &str, i16, bool); 6] = [
+        ("Скелет", "Некрополис", 4, true),
+        ("Живой мертвец", "Некрополис", 3, true),
+        ("Воздушный элементаль", "Сопряжение", 7, false),
+        ("Гремлин", "Башня", 4, false),
+        ("Каменная горгулья", "Башня", 6, false),
+        ("Джинн", "Башня", 7, false),
+    ];
+    let mut creature_ids = Vec::new();
+    for (name, town, speed, undead) in creatures {
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO creatures (name, town, speed, undead, portrait)
+             VALUES ($1, $2, $3, $4, '')
+             RETURNING id",
+        )
+        .bind(name)
+        .bind(town)
+        .bind(speed)
+        .bind(undead)
+        .fetch_one(&mut tx)
+        .await?;
+        creature_ids.push(id);
+    }
+
+    let armies = [
+        (
+            hero_ids[0],
+            [
+                (creature_ids[0], 120),
+                (creature_ids[1], 40),
+                (creature_ids[2], 12),
+            ],
+        ),
+        (
+            hero_ids[1],
+            [
+                (creature_ids[3], 30),
+                (creature_ids[4], 8),
+                (creature_ids[5], 2),
+            ],
+        ),
+    ];
+    for (hero_id, army) in armies {
+        for (slot, (creature_id, count)) in army.into_iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO hero_units (hero_id, slot, creature_id, count)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(hero_id)
+            .bind(slot as i16)
+            .bind(creature_id)
+            .bind(count)
+            .execute(&mut tx)
+            .await?;
+        }
+    }
+
+    for (slot, skill_id) in [skill_ids[0], skill_ids[1]].into_iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO hero_skills (hero_id, slot, skill_id, kind_id)
+             VALUES ($1, $2, $3, (SELECT kind_id FROM skills WHERE id = $3))",
+        )
+        .bind(hero_ids[0])
+        .bind(slot as i16)
+        .bind(skill_id)
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_pattern_escapes_wildcards() {
+        assert_eq!(contains_pattern(""), "%%");
+        assert_eq!(contains_pattern("Лук"), "%Лук%");
+        assert_eq!(contains_pattern("50%_off"), "%50\\%\\_off%");
+        assert_eq!(contains_pattern("a\\b"), "%a\\\\b%");
+    }
+}
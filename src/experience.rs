@@ -0,0 +1,76 @@
+/// Experience needed for levels 1 to 12. After that every level costs 20%
+/// more than the previous one, rounded down.
+const EXPERIENCE_TABLE: [u32; 12] = [
+    0, 1000, 2000, 3200, 4600, 6200, 8000, 10000, 12200, 14700, 17500, 20600,
+];
+
+/// The game stops levelling heroes up past this.
+pub const MAX_LEVEL: u8 = 74;
+
+/// Total experience a hero needs to reach `level`.
+pub fn experience_for_level(level: u8) -> u32 {
+    let level = level.clamp(1, MAX_LEVEL) as usize;
+    if level <= EXPERIENCE_TABLE.len() {
+        return EXPERIENCE_TABLE[level - 1];
+    }
+    let mut experience = EXPERIENCE_TABLE[EXPERIENCE_TABLE.len() - 1];
+    let mut step = experience - EXPERIENCE_TABLE[EXPERIENCE_TABLE.len() - 2];
+    for _ in EXPERIENCE_TABLE.len()..level {
+        step = step * 6 / 5;
+        experience += step;
+    }
+    experience
+}
+
+pub fn level_for_experience(experience: u32) -> u8 {
+    (1..MAX_LEVEL)
+        .find(|level| experience_for_level(level + 1) > experience)
+        .unwrap_or(MAX_LEVEL)
+}
+
+/// Experience left until the next level, or `None` at `MAX_LEVEL`.
+pub fn experience_to_next_level(experience: u32) -> Option<u32> {
+    let level = level_for_experience(experience);
+    (level < MAX_LEVEL).then(|| experience_for_level(level + 1) - experience)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experience_follows_the_table_then_grows_by_a_fifth() {
+        assert_eq!(experience_for_level(1), 0);
+        assert_eq!(experience_for_level(2), 1000);
+        assert_eq!(experience_for_level(12), 20600);
+        assert_eq!(experience_for_level(13), 24320);
+        assert_eq!(experience_for_level(14), 28784);
+        assert_eq!(experience_for_level(MAX_LEVEL), 1_508_362_195);
+    }
+
+    #[test]
+    fn experience_for_level_clamps_the_level() {
+        assert_eq!(experience_for_level(0), 0);
+        assert_eq!(experience_for_level(100), experience_for_level(MAX_LEVEL));
+    }
+
+    #[test]
+    fn level_changes_on_the_threshold() {
+        assert_eq!(level_for_experience(0), 1);
+        assert_eq!(level_for_experience(999), 1);
+        assert_eq!(level_for_experience(1000), 2);
+        assert_eq!(level_for_experience(24319), 12);
+        assert_eq!(level_for_experience(24320), 13);
+        assert_eq!(level_for_experience(u32::MAX), MAX_LEVEL);
+    }
+
+    #[test]
+    fn nothing_to_go_at_max_level() {
+        assert_eq!(experience_to_next_level(0), Some(1000));
+        assert_eq!(experience_to_next_level(1500), Some(500));
+        assert_eq!(
+            experience_to_next_level(experience_for_level(MAX_LEVEL)),
+            None
+        );
+    }
+}
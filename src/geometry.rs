@@ -14,7 +14,10 @@ pos! {
     H_PORTRAIT, 19, 19, 58, 64,
     H_NAME, 81, 20, 217, 34,
     H_CLASS, 81, 50, 218, 23,
+    LEVEL_UP_BUTTON, 298, 50, 18, 23,
+    H_SWITCHER_UP, 612, 72, 48, 12,
     H_SWITCHER_PORTRAIT, 612, 86, 48, 32,
+    H_SWITCHER_DOWN, 612, 446, 48, 12,
     SPEC_IMAGE, 18, 180, 44, 44,
     PSKILL_IMAGE, 32, 111, 42, 42,
     PSKILL_NAME, 32, 92, 42, 13,
@@ -22,14 +25,68 @@ pos! {
     SKILL_IMAGE, 18, 228, 44, 44,
     SKILL_TEXT, 67, 232, 89, 38,
     SKILL_BOX, 18, 228, 139, 46,
+    SECONDARY_STATS_BUTTON, 299, 228, 14, 46,
     MORALE_IMAGE, 182, 184, 42, 38,
     LUCK_IMAGE, 240, 184, 42, 38,
+    ITEM_RIGHT_HAND_1, 318, 60, 44, 44,
+    ITEM_RIGHT_HAND_2, 366, 60, 44, 44,
+    ITEM_HELMET, 444, 22, 44, 44,
+    ITEM_AMULET, 444, 72, 44, 44,
+    ITEM_ARMOR, 444, 122, 44, 44,
+    ITEM_SUPPORT_1, 499, 22, 44, 44,
+    ITEM_SUPPORT_2, 545, 22, 44, 44,
+    ITEM_SUPPORT_3, 545, 68, 44, 44,
+    ITEM_SUPPORT_4, 545, 114, 44, 44,
+    ITEM_LEFT_HAND_1, 499, 175, 44, 44,
+    ITEM_LEFT_HAND_2, 545, 175, 44, 44,
+    ITEM_CLOAK, 502, 232, 44, 44,
+    ITEM_MISC_1, 318, 135, 44, 44,
+    ITEM_MISC_2, 334, 186, 44, 44,
+    ITEM_MISC_3, 350, 237, 44, 44,
+    ITEM_MISC_4, 366, 288, 44, 44,
+    ITEM_MISC_5, 316, 288, 44, 44,
+    SPELLBOOK_BUTTON, 545, 300, 44, 44,
+    BACKPACK_ITEM, 338, 357, 44, 44,
+    BACKPACK_LEFT, 314, 356, 22, 46,
+    BACKPACK_RIGHT, 567, 356, 22, 46,
+    UNIT_IMAGE, 15, 485, 58, 64,
+    UNIT_COUNT, 15, 535, 58, 14,
     INFO_BOX, 8, 558, 656, 20,
-    FLAG_IMAGE, 606, 8, 58, 64
+    FLAG_IMAGE, 606, 8, 58, 64,
+    HERO_NEW_BUTTON, 606, 462, 20, 28,
+    HERO_DUPLICATE_BUTTON, 626, 462, 20, 28,
+    HERO_DELETE_BUTTON, 646, 462, 20, 28,
+    COMPARE_BUTTON, 612, 500, 48, 32
 }
 
 pub const WINDOW_SIZE: Vec2 = Vec2 { x: 672., y: 586. };
 pub const H_SWITCHER_PORTRAIT_OFFSET: Vec2 = Vec2 { x: 0., y: 54. };
+pub const H_SWITCHER_VISIBLE_HEROES: usize = 7;
 pub const PSKILL_OFFSET: Vec2 = Vec2 { x: 70., y: 0. };
 pub const SKILL_OFFSET_H: Vec2 = Vec2 { x: 142., y: 0. };
 pub const SKILL_OFFSET_V: Vec2 = Vec2 { x: 0., y: 48. };
+pub const UNIT_OFFSET: Vec2 = Vec2 { x: 66., y: 0. };
+pub const BACKPACK_OFFSET: Vec2 = Vec2 { x: 46., y: 0. };
+pub const BACKPACK_VISIBLE_ITEMS: usize = 5;
+pub const SPELLBOOK_PAGE_SIZE: usize = 6;
+
+/// Paper-doll slots, indexed by `ItemSlot as usize`.
+pub const ITEM_SLOTS: [Rect; 17] = [
+    ITEM_RIGHT_HAND_1,
+    ITEM_RIGHT_HAND_2,
+    ITEM_HELMET,
+    ITEM_AMULET,
+    ITEM_ARMOR,
+    ITEM_SUPPORT_1,
+    ITEM_SUPPORT_2,
+    ITEM_SUPPORT_3,
+    ITEM_SUPPORT_4,
+    ITEM_LEFT_HAND_1,
+    ITEM_LEFT_HAND_2,
+    ITEM_CLOAK,
+    ITEM_MISC_1,
+    ITEM_MISC_2,
+    ITEM_MISC_3,
+    ITEM_MISC_4,
+    ITEM_MISC_5,
+];
@@ -0,0 +1,48 @@
+use crate::skill::SkillKind;
+
+/// Might classes lean on attack and defence, magic ones on power and
+/// knowledge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClassKind {
+    Might,
+    Magic,
+}
+
+impl Default for ClassKind {
+    fn default() -> Self {
+        ClassKind::Might
+    }
+}
+
+impl ClassKind {
+    pub const ALL: [ClassKind; 2] = [ClassKind::Might, ClassKind::Magic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClassKind::Might => "Сила",
+            ClassKind::Magic => "Магия",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeroClass {
+    pub id: usize,
+    pub name: String,
+    /// One of [`crate::unit::TOWNS`].
+    pub town: String,
+    pub kind: ClassKind,
+    /// Chances (in percent) of attack, defence, power and knowledge going up
+    /// at levels 2-9.
+    pub low_level_growth: [u8; 4],
+    /// The same from level 10 on.
+    pub high_level_growth: [u8; 4],
+    /// Relative chances of being offered a new skill of each kind.
+    pub skill_weights: Vec<(SkillKind, u32)>,
+}
+
+impl PartialEq for HeroClass {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
@@ -1,24 +1,97 @@
 use eframe::{egui, epi};
 
-use crate::backend::BackendStatus;
-use crate::backend::DemoBackend;
+use crate::backend::{BackendStatus, DemoBackend, HeroBackend};
+use crate::backpack::{Item, ItemSlot};
+use crate::character::Character;
+use crate::config::{DbConfig, DB_CONFIG_STORAGE_KEY};
+use crate::experience::{
+    experience_for_level, experience_to_next_level, level_for_experience, MAX_LEVEL,
+};
 use crate::geometry::*;
 use crate::hero::*;
+use crate::level_up::{level_up, Rng};
+use crate::player::Player;
+use crate::secondary_stats::secondary_stats;
+use crate::skill_offers::{skill_offers, SkillOffer};
+use crate::spell::{spell_cast, Spell, SpellCast, SpellSchool};
 use crate::static_assets::StaticAssets;
+use crate::stats::{
+    auto_mana_max, clamp_auto_mana, mana_max, sprite_index, stat_value, Stat, LUCK_MORALE_RANGE,
+};
+use crate::unit::Unit;
 use crate::utils::*;
+use crate::validation::check_change;
 use crate::widgets::*;
 
-#[derive(Default)]
 pub struct HeroViewer {
     static_assets: StaticAssets,
     hero: Option<Hero>,
     hero_select_buttons: Vec<HeroSelectButton>,
     selected_hero_idx: usize,
+    /// Index of the first hero shown in the switcher.
+    switcher_offset: usize,
+    /// Index of the hero whose portrait is being dragged in the switcher.
+    dragged_hero: Option<usize>,
     player_id: usize,
     pixels_per_point: f32,
     search_query: String,
-    backend: DemoBackend,
+    backpack_offset: usize,
+    /// Hero and seed of the skill offers to make once the candidates are
+    /// loaded.
+    pending_skill_offers: Option<(usize, u64)>,
+    /// Offers on screen and the hero they were made to.
+    skill_offers: Option<(usize, Vec<SkillOffer>)>,
+    /// Spellbook tab, `None` for all schools.
+    spellbook_school: Option<SpellSchool>,
+    spellbook_page: usize,
+    comparison_open: bool,
+    /// Ids of the heroes in the left and the right column of the comparison.
+    compared_heroes: [usize; 2],
+    /// Copies of those two heroes, fetched when the window opens or the
+    /// selection changes.
+    compared_hero_copies: Option<[Hero; 2]>,
+    backend: Box<dyn HeroBackend>,
     backend_messages: Vec<String>,
+    db_config: DbConfig,
+    /// `db_config` as it was loaded, to tell what the settings popup changed.
+    loaded_db_config: DbConfig,
+    /// What storage held on start, see [`DbConfig::storage_string`].
+    stored_db_config: String,
+}
+
+impl Default for HeroViewer {
+    fn default() -> Self {
+        Self::with_backend(Box::new(DemoBackend::default()))
+    }
+}
+
+impl HeroViewer {
+    pub fn with_backend(backend: Box<dyn HeroBackend>) -> Self {
+        Self {
+            static_assets: Default::default(),
+            hero: Default::default(),
+            hero_select_buttons: Default::default(),
+            selected_hero_idx: Default::default(),
+            switcher_offset: 0,
+            dragged_hero: None,
+            player_id: Default::default(),
+            pixels_per_point: Default::default(),
+            search_query: Default::default(),
+            backpack_offset: 0,
+            pending_skill_offers: None,
+            skill_offers: None,
+            spellbook_school: None,
+            spellbook_page: 0,
+            comparison_open: false,
+            compared_heroes: [0; 2],
+            compared_hero_copies: None,
+            backend,
+            backend_messages: Default::default(),
+            db_config: Default::default(),
+            loaded_db_config: Default::default(),
+            stored_db_config: Default::default(),
+        }
+    }
 }
 
 impl epi::App for HeroViewer {
@@ -26,9 +99,16 @@ impl epi::App for HeroViewer {
         &mut self,
         ctx: &egui::CtxRef,
         frame: &mut epi::Frame<'_>,
-        _storage: Option<&dyn epi::Storage>,
+        storage: Option<&dyn epi::Storage>,
     ) {
         self.pixels_per_point = 1.0;
+        self.stored_db_config = storage
+            .and_then(|s| s.get_string(DB_CONFIG_STORAGE_KEY))
+            .unwrap_or_default();
+        let (db_config, errors) = DbConfig::load(&self.stored_db_config);
+        self.db_config = db_config.clone();
+        self.loaded_db_config = db_config;
+        self.backend_messages.extend(errors);
 
         self.static_assets.init(frame);
 
@@ -46,8 +126,13 @@ impl epi::App for HeroViewer {
         self.process_global_hotkeys(ctx.input());
 
         self.backend.update(frame);
-        if let Ok(msg) = self.backend.messages_receiver.try_recv() {
+        if let Some(msg) = self.backend.poll_message() {
             self.backend_messages.push(msg);
+            // A failed write reloads the backend cache, so pick up whatever it holds now.
+            if let Some(hero_id) = self.hero.as_ref().map(|h| h.id) {
+                let hero = self.backend.get_hero(hero_id);
+                self.set_hero(hero);
+            }
         }
 
         ctx.set_pixels_per_point(self.pixels_per_point);
@@ -75,22 +160,39 @@ impl epi::App for HeroViewer {
                 } else if status == BackendStatus::Connecting {
                     ui.put(INFO_BOX, egui::Label::new("Connecting..."));
                     return;
+                } else if status == BackendStatus::QueryInProgress {
+                    ui.put(INFO_BOX, egui::Label::new("Сохранение..."));
                 } else if let Some(msg) = self.backend_messages.last() {
                     ui.put(INFO_BOX, egui::Label::new(msg));
                 }
 
                 self.hero_select_buttons = self.backend.get_player_heroes(self.player_id);
                 self.show_hero_switcher(ui);
-                self.show_portrait_name_class(ui);
+                self.show_roster_buttons(ui, frame);
+                self.show_comparison(ui);
+                self.show_portrait_name_class(ui, frame);
+                self.show_level_up_button(ui);
+                self.show_skill_offers(ui);
                 self.show_primary_skills(ui);
                 self.show_xp(ui);
                 self.show_mana(ui);
+                self.show_secondary_stats(ui);
                 self.show_specialty(ui, frame);
                 self.show_skills(ui, frame);
+                self.show_army(ui, frame);
+                self.show_artifacts(ui, frame);
+                self.show_spellbook(ui, frame);
                 self.show_luck_morale(ui);
             });
     }
 
+    fn save(&mut self, storage: &mut dyn epi::Storage) {
+        let stored = self
+            .db_config
+            .storage_string(&self.loaded_db_config, &self.stored_db_config);
+        storage.set_string(DB_CONFIG_STORAGE_KEY, stored);
+    }
+
     fn name(&self) -> &str {
         "HoMM3 Hero Viewer"
     }
@@ -107,43 +209,340 @@ macro_rules! get_or_return {
 
 impl HeroViewer {
     fn show_hero_switcher(&mut self, ui: &mut egui::Ui) {
-        for (idx, hero_button) in self.hero_select_buttons.iter().enumerate() {
-            if ui
-                .put(
-                    H_SWITCHER_PORTRAIT.translate(H_SWITCHER_PORTRAIT_OFFSET * idx as f32),
-                    hero_button.portrait.image_button(),
-                )
-                .clicked()
+        let heroes_count = self.hero_select_buttons.len();
+        // Heroes move around when they are reordered, created or deleted.
+        let selected = self.hero.as_ref().and_then(|hero| {
+            self.hero_select_buttons
+                .iter()
+                .position(|b| b.id == hero.id)
+        });
+        if let Some(idx) = selected {
+            self.selected_hero_idx = idx;
+        }
+
+        let max_offset = heroes_count.saturating_sub(H_SWITCHER_VISIBLE_HEROES);
+        self.switcher_offset = self.switcher_offset.min(max_offset);
+        if max_offset > 0 {
+            let up_button = egui::Button::new("▲").fill(egui::Color32::TRANSPARENT);
+            if ui.put(H_SWITCHER_UP, up_button).clicked() {
+                self.switcher_offset = self.switcher_offset.saturating_sub(1);
+            }
+            let down_button = egui::Button::new("▼").fill(egui::Color32::TRANSPARENT);
+            if ui.put(H_SWITCHER_DOWN, down_button).clicked() {
+                self.switcher_offset = (self.switcher_offset + 1).min(max_offset);
+            }
+        }
+
+        // Up/Down and Tab/Shift+Tab cycle through the heroes unless some
+        // text field is being typed into.
+        if heroes_count > 0 && ui.memory().focus().is_none() {
+            let input = ui.input();
+            let tab = input.key_pressed(egui::Key::Tab);
+            let step = if input.key_pressed(egui::Key::ArrowDown) || (tab && !input.modifiers.shift)
             {
-                self.selected_hero_idx = idx;
-                self.hero = Some(get_or_return!(self.backend.get_hero(hero_button.id)));
+                Some(1)
+            } else if input.key_pressed(egui::Key::ArrowUp) || (tab && input.modifiers.shift) {
+                Some(heroes_count - 1)
+            } else {
+                None
+            };
+            if let Some(step) = step {
+                let idx = match selected {
+                    Some(idx) => (idx + step) % heroes_count,
+                    None => 0,
+                };
+                self.select_hero(idx);
+            }
+        }
+
+        let pointer = ui.input().pointer.hover_pos();
+        let mut drop_target = None;
+        let mut clicked = None;
+        for slot in 0..H_SWITCHER_VISIBLE_HEROES {
+            let idx = self.switcher_offset + slot;
+            let hero_button = match self.hero_select_buttons.get(idx) {
+                Some(hero_button) => hero_button,
+                None => break,
+            };
+            let rect = H_SWITCHER_PORTRAIT.translate(H_SWITCHER_PORTRAIT_OFFSET * slot as f32);
+            let button = hero_button
+                .portrait
+                .image_button()
+                .sense(egui::Sense::click_and_drag());
+            let response = ui.put(rect, button);
+            if response.clicked() {
+                clicked = Some(idx);
             }
+            if response.drag_started() {
+                self.dragged_hero = Some(idx);
+            }
+            if self.dragged_hero.is_some() && pointer.map_or(false, |p| rect.contains(p)) {
+                drop_target = Some(idx);
+                selected_frame_around(ui, rect);
+            }
+        }
+        if let Some(idx) = clicked {
+            self.select_hero(idx);
+        }
+
+        if let Some(dragged) = self.dragged_hero {
+            if ui.input().pointer.any_released() {
+                self.dragged_hero = None;
+                let hero_button = self.hero_select_buttons.get(dragged);
+                if let (Some(hero_button), Some(target)) = (hero_button, drop_target) {
+                    if target != dragged {
+                        self.backend.move_hero(hero_button.id, target);
+                    }
+                }
+            }
+        }
+
+        if selected.is_some() {
+            let slot = self.selected_hero_idx.checked_sub(self.switcher_offset);
+            if let Some(slot) = slot.filter(|s| *s < H_SWITCHER_VISIBLE_HEROES) {
+                let selected_hero =
+                    H_SWITCHER_PORTRAIT.translate(H_SWITCHER_PORTRAIT_OFFSET * slot as f32);
+                selected_frame_around(ui, selected_hero);
+            }
+        }
+    }
+
+    /// Shows `hero`. Skill offers made to another hero are dropped.
+    fn set_hero(&mut self, hero: Option<Hero>) {
+        if hero.as_ref().map(|h| h.id) != self.hero.as_ref().map(|h| h.id) {
+            self.pending_skill_offers = None;
+            self.skill_offers = None;
         }
-        let selected_hero = H_SWITCHER_PORTRAIT
-            .translate(H_SWITCHER_PORTRAIT_OFFSET * self.selected_hero_idx as f32);
-        selected_frame_around(ui, selected_hero);
+        self.hero = hero;
     }
 
-    fn show_portrait_name_class(&self, ui: &mut egui::Ui) {
+    /// Shows the hero at `idx` in the switcher, scrolling it into view.
+    fn select_hero(&mut self, idx: usize) {
+        let hero_button = get_or_return!(self.hero_select_buttons.get(idx));
+        let hero = get_or_return!(self.backend.get_hero(hero_button.id));
+        self.set_hero(Some(hero));
+        self.selected_hero_idx = idx;
+        if idx < self.switcher_offset {
+            self.switcher_offset = idx;
+        } else if idx >= self.switcher_offset + H_SWITCHER_VISIBLE_HEROES {
+            self.switcher_offset = idx + 1 - H_SWITCHER_VISIBLE_HEROES;
+        }
+    }
+
+    fn show_roster_buttons(&mut self, ui: &mut egui::Ui, frame: &mut epi::Frame) {
+        let button = |text| egui::Button::new(text).fill(egui::Color32::TRANSPARENT);
+
+        let new_response = ui
+            .put(HERO_NEW_BUTTON, button("➕"))
+            .on_hover_text("Новый герой");
+        let classes = self.backend.get_classes().unwrap_or_default();
+        show_hero_create_window(
+            ui,
+            new_response.clicked(),
+            &classes,
+            self.player_id,
+            self.backend.as_mut(),
+            frame,
+        );
+
         let hero = get_or_return!(&self.hero);
+        let duplicate_response = ui
+            .put(HERO_DUPLICATE_BUTTON, button("⧉"))
+            .on_hover_text("Копия героя");
+        if duplicate_response.clicked() {
+            self.backend.duplicate_hero(hero.id);
+        }
 
-        ui.put(H_PORTRAIT, hero.character.portrait.image());
-        let hero_name_label = egui::Label::new(
-            egui::RichText::new(&hero.character.name)
-                .heading()
-                .color(H_GOLD),
+        let delete_response = ui
+            .put(HERO_DELETE_BUTTON, button("🗑"))
+            .on_hover_text("Удалить героя");
+        let question = format!(
+            "Удалить героя {} со всем, что у него есть?",
+            hero.character.name
         );
-        ui.put(H_NAME, hero_name_label);
+        if show_confirm_window(ui, delete_response, "Удалить героя", &question) {
+            self.backend.delete_hero(hero.id);
+            self.set_hero(None);
+        }
+    }
+
+    fn show_comparison(&mut self, ui: &mut egui::Ui) {
+        let button = egui::Button::new("⚖").fill(egui::Color32::TRANSPARENT);
+        let button_response = ui
+            .put(COMPARE_BUTTON, button)
+            .on_hover_text("Сравнить героев");
+        if button_response.clicked() {
+            self.comparison_open = !self.comparison_open;
+            self.compared_hero_copies = None;
+        }
+        if !self.comparison_open {
+            return;
+        }
+
+        show_hero_comparison_window(
+            ui.ctx(),
+            &mut self.comparison_open,
+            &self.hero_select_buttons,
+            &mut self.compared_heroes,
+            &mut self.compared_hero_copies,
+            self.backend.as_mut(),
+            &self.static_assets,
+        );
+    }
+
+    fn show_portrait_name_class(&mut self, ui: &mut egui::Ui, frame: &mut epi::Frame) {
+        let hero = get_or_return!(&mut self.hero);
+
+        let portrait_response = ui
+            .put(H_PORTRAIT, hero.character.portrait.image_button())
+            .on_hover_text("Сменить портреты");
+        let opened = portrait_response
+            .clicked()
+            .then(|| (hero.id, &hero.character));
+        let saved = show_portrait_edit_window(ui, opened, self.backend.as_mut(), frame);
+        if let Some((portrait, portrait_small)) = saved {
+            hero.character.portrait = portrait;
+            hero.character.portrait_small = portrait_small;
+        }
+
+        let hero_name_edit = egui::TextEdit::singleline(&mut hero.character.name)
+            .frame(false)
+            .text_style(egui::TextStyle::Heading)
+            .text_color(H_GOLD);
+        let name_response = ui.put(H_NAME, hero_name_edit);
+        if name_response.lost_focus() {
+            let saved = get_or_return!(self.backend.get_hero(hero.id)).character;
+            if saved.name != hero.character.name {
+                if let Err(e) = self.backend.set_hero_character(hero.id, &hero.character) {
+                    hero.character.name = saved.name;
+                    self.backend_messages.push(e);
+                }
+            }
+        }
+
         let hero_class_label = egui::Label::new(&format!(
             "{} {}-го уровня",
             hero.character.class, hero.level
-        ));
-        ui.put(H_CLASS, hero_class_label);
+        ))
+        .sense(egui::Sense::click());
+        let class_response = ui
+            .put(H_CLASS, hero_class_label)
+            .on_hover_text("Сменить класс");
+        let mut new_class = None;
+        let mut edit_value = None;
+        show_selection_window(ui, class_response, "Класс", |ui| {
+            if ui.button("➕").on_hover_text("Новый класс").clicked() {
+                edit_value = Some(None);
+            }
+            for class in self.backend.get_classes().unwrap_or_default() {
+                ui.horizontal(|ui| {
+                    let selected = class.name == hero.character.class;
+                    if ui.small_button("📝").clicked() {
+                        edit_value = Some(Some(class.clone()));
+                    }
+                    let text = format!("{} ({})", class.name, class.town);
+                    if ui.selectable_label(selected, text).clicked() {
+                        new_class = Some(class.name);
+                    }
+                });
+            }
+        });
+        show_class_edit_window(ui, edit_value, self.backend.as_mut());
+        if let Some(class) = new_class {
+            let character = Character {
+                class,
+                ..hero.character.clone()
+            };
+            match self.backend.set_hero_character(hero.id, &character) {
+                Ok(()) => {
+                    hero.character = character;
+                    hero.spec = Default::default();
+                }
+                Err(e) => self.backend_messages.push(e),
+            }
+        }
+    }
+
+    fn show_level_up_button(&mut self, ui: &mut egui::Ui) {
+        let hero = get_or_return!(&mut self.hero);
+        if hero.level >= MAX_LEVEL {
+            return;
+        }
+
+        let button = egui::Button::new("⏫").fill(egui::Color32::TRANSPARENT);
+        let response = ui
+            .put(LEVEL_UP_BUTTON, button)
+            .on_hover_text("Следующий уровень");
+        if response.clicked() {
+            let mut rng = Rng::new(ui.input().time.to_bits());
+            let class = self.backend.get_class(&hero.character.class);
+            let (experience, level, pskills) = (hero.experience, hero.level, hero.pskills);
+            if let Some(pskill) = level_up(hero, class.as_ref(), &mut rng) {
+                let value = hero.pskills[pskill];
+                match self.backend.set_hero_pskill(hero.id, pskill, value) {
+                    Ok(()) => {
+                        if let Err(e) = self.backend.set_hero_xp(hero.id, hero.experience) {
+                            self.backend_messages.push(e);
+                        }
+                        self.pending_skill_offers = Some((hero.id, rng.next_u64()));
+                    }
+                    Err(e) => {
+                        hero.experience = experience;
+                        hero.level = level;
+                        hero.pskills = pskills;
+                        self.backend_messages.push(e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn show_skill_offers(&mut self, ui: &mut egui::Ui) {
+        let hero = get_or_return!(&mut self.hero);
+
+        if let Some((hero_id, seed)) = self.pending_skill_offers {
+            if hero_id != hero.id {
+                self.pending_skill_offers = None;
+                return;
+            }
+            let catalogue = get_or_return!(self.backend.get_skill_offer_candidates(hero.id));
+            let class = self.backend.get_class(&hero.character.class);
+            let mut rng = Rng::new(seed);
+            let offers = skill_offers(
+                &hero.skills,
+                &catalogue,
+                class.as_ref(),
+                &mut hero.offer_streaks,
+                &mut rng,
+            );
+            self.backend
+                .set_hero_offer_streaks(hero.id, hero.offer_streaks);
+            self.skill_offers = Some((hero.id, offers));
+            self.pending_skill_offers = None;
+        }
+        let offers = match &self.skill_offers {
+            Some((hero_id, offers)) if *hero_id == hero.id && !offers.is_empty() => offers,
+            _ => return,
+        };
+
+        if let Some(picked) = show_skill_offers_window(ui, offers) {
+            if let Some(offer) = picked.map(|i| offers[i].clone()) {
+                let skill_id = Some(offer.skill.id);
+                match self.backend.set_hero_skill(hero.id, offer.slot, skill_id) {
+                    Ok(()) => {
+                        hero.skills[offer.slot] = Some(offer.skill);
+                        clamp_auto_mana(hero);
+                    }
+                    Err(e) => self.backend_messages.push(e),
+                }
+            }
+            self.skill_offers = None;
+        }
     }
 
     fn show_specialty(&mut self, ui: &mut egui::Ui, frame: &mut epi::Frame) {
         let hero = get_or_return!(&mut self.hero);
-        let mut set_new_value = false;
+        let mut new_spec = None;
 
         let widget_response = ui.put(SPEC_IMAGE, hero.spec.image.image_button());
 
@@ -173,8 +572,7 @@ impl HeroViewer {
                     if e.clicked() {
                         edit_value = Some(Some(s.clone()));
                     } else if b.clicked() {
-                        hero.spec = s.clone();
-                        set_new_value = true;
+                        new_spec = Some(s.clone());
                     }
                 }
             });
@@ -188,10 +586,16 @@ impl HeroViewer {
             ui.add(spec_bottom_label);
         });
 
-        show_spec_edit_window(ui, edit_value, &mut self.backend, frame);
+        show_spec_edit_window(ui, edit_value, self.backend.as_mut(), frame);
 
-        if set_new_value {
-            self.backend.set_hero_spec(hero.id, &hero.spec.name);
+        if let Some(spec) = new_spec {
+            match self.backend.set_hero_spec(hero.id, spec.id) {
+                Ok(()) => {
+                    hero.spec = spec;
+                    clamp_auto_mana(hero);
+                }
+                Err(e) => self.backend_messages.push(e),
+            }
         }
     }
 
@@ -199,16 +603,27 @@ impl HeroViewer {
         let hero = get_or_return!(&mut self.hero);
 
         let widget_response = ui.put(SKILL_IMAGE, self.static_assets.xp.image_button());
-        if let Some(new_value) = show_xp_popup(ui, widget_response, hero.experience) {
-            hero.experience = new_value;
-            self.backend.set_hero_xp(hero.id, new_value);
+        let set_xp = |h: &mut Hero, value: u32| {
+            h.experience = value.min(experience_for_level(MAX_LEVEL));
+            h.level = level_for_experience(h.experience);
+        };
+        let check = |value| check_change(hero, |h| set_xp(h, value));
+        if let Some(new_value) = show_xp_popup(ui, widget_response, hero.experience, check) {
+            match self.backend.set_hero_xp(hero.id, new_value) {
+                Ok(()) => set_xp(hero, new_value),
+                Err(e) => self.backend_messages.push(e),
+            }
         }
+        let hover_text = match experience_to_next_level(hero.experience) {
+            Some(left) => format!("До следующего уровня: {}", left),
+            None => "Максимальный уровень".to_string(),
+        };
         ui.allocate_ui_at_rect(SKILL_TEXT, |ui| {
             let xp_top_label = egui::Label::new("Опыт");
             ui.add(xp_top_label);
             ui.add_space(4.);
             let xp_bottom_label = egui::Label::new(&hero.experience.to_string());
-            ui.add(xp_bottom_label)
+            ui.add(xp_bottom_label).on_hover_text(hover_text)
         });
     }
 
@@ -219,27 +634,97 @@ impl HeroViewer {
             SKILL_IMAGE.translate(SKILL_OFFSET_H),
             self.static_assets.mana.image_button(),
         );
-        if let Some((new_current, new_max)) =
-            show_mana_popup(ui, widget_response, hero.mana_current, hero.mana_max)
-        {
-            hero.mana_current = new_current;
-            hero.mana_max = new_max;
-            self.backend.set_hero_mana(hero.id, new_current, new_max);
+        let set_mana = |h: &mut Hero, current, max: Option<u16>| {
+            h.mana_current = current;
+            h.mana_max = max.unwrap_or(h.mana_max);
+            h.mana_max_manual = max.is_some();
+        };
+        let auto_max = auto_mana_max(hero);
+        let manual_max = Some(hero.mana_max).filter(|_| hero.mana_max_manual);
+        let check = |current, max| check_change(hero, |h| set_mana(h, current, max));
+        let new_values = show_mana_popup(
+            ui,
+            widget_response,
+            hero.mana_current,
+            manual_max,
+            auto_max,
+            check,
+        );
+        if let Some((new_current, new_max)) = new_values {
+            match self.backend.set_hero_mana(hero.id, new_current, new_max) {
+                Ok(()) => set_mana(hero, new_current, new_max),
+                Err(e) => self.backend_messages.push(e),
+            }
         }
+        let (max_flag, hover_text) = if hero.mana_max_manual {
+            let text = format!("Максимум задан вручную, по правилам: {}", auto_max);
+            ("*", text)
+        } else {
+            ("", "Максимум по знаниям и навыку «Интеллект»".to_string())
+        };
         ui.allocate_ui_at_rect(SKILL_TEXT.translate(SKILL_OFFSET_H), |ui| {
             let mana_top_label = egui::Label::new("Очки магии");
             ui.add(mana_top_label);
             ui.add_space(4.);
-            let mana_bottom_label =
-                egui::Label::new(&format!("{}/{}", hero.mana_current, hero.mana_max));
-            ui.add(mana_bottom_label);
+            let mana_text = format!("{}/{}{}", hero.mana_current, mana_max(hero), max_flag);
+            let mana_bottom_label = egui::Label::new(&mana_text);
+            ui.add(mana_bottom_label).on_hover_text(hover_text);
         });
     }
 
+    fn show_secondary_stats(&mut self, ui: &mut egui::Ui) {
+        let hero = get_or_return!(&self.hero);
+        let stats = secondary_stats(hero);
+
+        let button = egui::Button::new("ℹ").fill(egui::Color32::TRANSPARENT);
+        let button_response = ui
+            .put(SECONDARY_STATS_BUTTON, button)
+            .on_hover_text("Вторичные характеристики");
+        show_selection_window(
+            ui,
+            button_response,
+            "Вторичные характеристики",
+            |ui| {
+                let army = match stats.army_speed {
+                    Some(speed) => format!("Самый медленный отряд: скорость {}", speed),
+                    None => "Армии нет".to_string(),
+                };
+                egui::Grid::new("secondary_stats")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Очки передвижения");
+                        ui.label(stats.movement.to_string()).on_hover_text(format!(
+                            "{}: {}\nЛогистика: {:+}",
+                            army,
+                            stats.base_movement,
+                            stats.movement - stats.base_movement
+                        ));
+                        ui.end_row();
+
+                        ui.label("Штраф местности");
+                        ui.label(format!("-{}%", stats.terrain_penalty_cut))
+                            .on_hover_text("Поиск пути");
+                        ui.end_row();
+
+                        ui.label("Очки магии в день");
+                        ui.label(stats.mana_regen.to_string())
+                            .on_hover_text("1 и ещё Мистицизм");
+                        ui.end_row();
+
+                        ui.label("Радиус обзора");
+                        ui.label(stats.scouting_radius.to_string())
+                            .on_hover_text("5 и ещё Разведка");
+                        ui.end_row();
+                    });
+            },
+        );
+    }
+
     fn show_primary_skills(&mut self, ui: &mut egui::Ui) {
         let hero = get_or_return!(&mut self.hero);
+        let stats = Stat::PRIMARY.map(|stat| stat_value(hero, stat));
 
-        for (i, ((name, image), value)) in ["Атака", "Защита", "Магия", "Знания"]
+        for (i, ((computed, image), value)) in stats
             .iter()
             .zip(
                 self.static_assets
@@ -248,7 +733,7 @@ impl HeroViewer {
                     .map(|i| i.image_button())
                     .collect::<Vec<_>>(),
             )
-            .zip(&mut hero.pskills)
+            .zip(hero.pskills)
             .enumerate()
         {
             let offset = PSKILL_OFFSET * i as f32;
@@ -257,15 +742,28 @@ impl HeroViewer {
             let value_rect = PSKILL_VALUE.translate(offset);
 
             let image_button_response = ui.put(image_rect, image);
-            if let Some(new_value) = show_pskill_popup(ui, image_button_response, *value) {
-                *value = new_value;
-                self.backend.set_hero_pskill(hero.id, i, new_value);
+            let check = |new_value| {
+                check_change(hero, |h| {
+                    h.pskills[i] = new_value;
+                    clamp_auto_mana(h);
+                })
+            };
+            if let Some(new_value) = show_pskill_popup(ui, image_button_response, value, check) {
+                match self.backend.set_hero_pskill(hero.id, i, new_value) {
+                    Ok(()) => {
+                        hero.pskills[i] = new_value;
+                        clamp_auto_mana(hero);
+                    }
+                    Err(e) => self.backend_messages.push(e),
+                }
             }
 
-            let name_label = egui::Label::new(egui::RichText::new(*name).color(H_GOLD));
+            let name_text = egui::RichText::new(computed.stat.name()).color(H_GOLD);
+            let name_label = egui::Label::new(name_text);
             ui.put(name_rect, name_label);
-            let value_label = egui::Label::new(&value.to_string());
-            ui.put(value_rect, value_label);
+            let value_label = egui::Label::new(&computed.label());
+            ui.put(value_rect, value_label)
+                .on_hover_text(computed.breakdown());
         }
     }
 
@@ -273,12 +771,11 @@ impl HeroViewer {
         let hero = get_or_return!(&mut self.hero);
 
         let mut edit_skill = None;
+        let mut result = None;
 
-        for (i, skill) in hero.skills.iter_mut().enumerate() {
+        for (i, skill) in hero.skills.iter().enumerate() {
             let offset = SKILL_OFFSET_V * (i % 4 + 1) as f32 + SKILL_OFFSET_H * (i / 4) as f32;
 
-            let mut set_new_value = false;
-
             let widget_response = if let Some(skill) = skill {
                 let image = skill.image.image_button();
                 ui.put(SKILL_IMAGE.translate(offset), image)
@@ -295,8 +792,21 @@ impl HeroViewer {
                         return;
                     }
                     if ui.button("❌").clicked() {
-                        *skill = None;
-                        set_new_value = true;
+                        result = Some(self.backend.set_hero_skill(hero.id, i, None));
+                    }
+                    if let Some(skill) = skill {
+                        if let Some(mastery) = skill.mastery.prev() {
+                            if ui.button("⬇").on_hover_text(mastery.name()).clicked() {
+                                result =
+                                    Some(self.backend.set_hero_skill_mastery(hero.id, i, mastery));
+                            }
+                        }
+                        if let Some(mastery) = skill.mastery.next() {
+                            if ui.button("⬆").on_hover_text(mastery.name()).clicked() {
+                                result =
+                                    Some(self.backend.set_hero_skill_mastery(hero.id, i, mastery));
+                            }
+                        }
                     }
                 });
 
@@ -313,39 +823,380 @@ impl HeroViewer {
                     for s in search_range {
                         let is_selected =
                             skill.as_ref().map(|s2| s2.id == s.id).unwrap_or_default();
+                        let (e, b) = show_selectable_block(ui, &s.image, s.title(), is_selected);
+                        if e.clicked() {
+                            edit_skill = Some(Some(s.clone()));
+                            return;
+                        } else if b.clicked() {
+                            let skill_id = Some(s.id);
+                            result = Some(self.backend.set_hero_skill(hero.id, i, skill_id));
+                        }
+                    }
+                });
+            });
+
+            if let Some(skill) = skill {
+                ui.allocate_ui_at_rect(SKILL_TEXT.translate(offset), |ui| {
+                    let spec_top_label = egui::Label::new(&skill.kind.name);
+                    ui.add(spec_top_label);
+                    ui.add_space(4.);
+                    let spec_bottom_label = egui::Label::new(skill.mastery.name());
+                    ui.add(spec_bottom_label);
+                });
+            }
+        }
+
+        match result {
+            Some(Ok(())) => {
+                if let Some(updated_hero) = self.backend.get_hero(hero.id) {
+                    *hero = updated_hero;
+                }
+            }
+            Some(Err(e)) => self.backend_messages.push(e),
+            None => {}
+        }
+        show_skill_edit_window(ui, edit_skill, self.backend.as_mut(), frame);
+    }
+
+    fn show_army(&mut self, ui: &mut egui::Ui, frame: &mut epi::Frame) {
+        let hero = get_or_return!(&mut self.hero);
+
+        let mut edit_creature = None;
+
+        for (i, unit) in hero.units.iter_mut().enumerate() {
+            let offset = UNIT_OFFSET * i as f32;
+
+            let mut set_new_value = false;
+
+            let widget_response = if let Some(unit) = unit {
+                let image = unit.creature.portrait.image_button();
+                ui.put(UNIT_IMAGE.translate(offset), image)
+            } else {
+                let button = egui::Button::new("").fill(egui::Color32::TRANSPARENT);
+                ui.put(UNIT_IMAGE.translate(offset), button)
+            };
+
+            show_selection_window(ui, widget_response, &format!("Отряд {}", i + 1), |ui| {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.search_query);
+                    if ui.button("➕").clicked() {
+                        edit_creature = Some(None);
+                        return;
+                    }
+                    if ui.button("❌").clicked() {
+                        *unit = None;
+                        set_new_value = true;
+                    }
+                });
+
+                let rows = get_or_return!(self.backend.get_creature_row_count(&self.search_query));
+                let scroll_area = egui::ScrollArea::vertical().auto_shrink([false, true]);
+                scroll_area.show_rows(ui, SKILL_BOX.height(), rows, |ui, range| {
+                    let search_range =
+                        get_or_return!(self.backend.get_creature_range(&self.search_query, &range));
+                    for c in search_range {
+                        let is_selected =
+                            unit.as_ref().map(|u| u.creature == *c).unwrap_or_default();
                         let (e, b) = show_selectable_block(
                             ui,
-                            &s.image,
-                            &format!("{}\n{} ступени", s.name, s.level),
+                            &c.portrait,
+                            &format!("{}\n{}, скорость {}", c.name, c.town, c.speed),
                             is_selected,
                         );
                         if e.clicked() {
-                            edit_skill = Some(Some(s.clone()));
+                            edit_creature = Some(Some(c.clone()));
                             return;
                         } else if b.clicked() {
-                            *skill = Some(s.clone());
+                            let count = unit.as_ref().map(|u| u.count).unwrap_or(1);
+                            *unit = Some(Unit {
+                                creature: c.clone(),
+                                count,
+                            });
                             set_new_value = true;
                         }
                     }
                 });
+            });
 
-                if set_new_value {
-                    self.backend
-                        .set_hero_skill(hero.id, i, skill.as_ref().map(|s| s.id))
+            if let Some(unit) = unit {
+                let count_text = egui::RichText::new(unit.count.to_string()).small();
+                let count_button = egui::Button::new(count_text).fill(egui::Color32::TRANSPARENT);
+                let count_response = ui.put(UNIT_COUNT.translate(offset), count_button);
+                if let Some(count) = show_unit_count_popup(ui, count_response, unit.count) {
+                    unit.count = count;
+                    set_new_value = true;
                 }
+            }
+
+            if set_new_value {
+                let new_unit = unit.as_ref().map(|u| (u.creature.id, u.count));
+                if let Err(e) = self.backend.set_hero_unit(hero.id, i, new_unit) {
+                    self.backend_messages.push(e);
+                    *unit = self
+                        .backend
+                        .get_hero(hero.id)
+                        .and_then(|h| h.units[i].clone());
+                }
+            }
+        }
+        show_creature_edit_window(ui, edit_creature, self.backend.as_mut(), frame);
+    }
+
+    fn show_artifacts(&mut self, ui: &mut egui::Ui, frame: &mut epi::Frame) {
+        let hero = get_or_return!(&mut self.hero);
+
+        let mut edit_item = None;
+        let mut new_backpack = None;
+
+        for slot in ItemSlot::ALL {
+            let item = &hero.backpack.equipped[slot as usize];
+            let rect = ITEM_SLOTS[slot as usize];
+            let widget_response = if let Some(item) = item {
+                let hover_text = format!("{}\nПКМ — снять", item_description(item));
+                ui.put(rect, item.image.image_button())
+                    .on_hover_text(hover_text)
+            } else {
+                let button = egui::Button::new("").fill(egui::Color32::TRANSPARENT);
+                ui.put(rect, button).on_hover_text(slot.kind().name())
+            };
+            if widget_response.secondary_clicked() && item.is_some() {
+                let mut backpack = hero.backpack.clone();
+                backpack.unequip(slot);
+                new_backpack = Some(backpack);
+            }
+
+            let title = format!("Артефакт: {}", slot.kind().name());
+            show_selection_window(ui, widget_response, &title, |ui| {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.search_query);
+                    if ui.button("➕").clicked() {
+                        edit_item = Some(None);
+                        return;
+                    }
+                    if ui.button("❌").clicked() {
+                        let mut backpack = hero.backpack.clone();
+                        backpack.unequip(slot);
+                        new_backpack = Some(backpack);
+                    }
+                });
+
+                let rows = get_or_return!(self
+                    .backend
+                    .get_item_row_count(Some(slot.kind()), &self.search_query));
+                let scroll_area = egui::ScrollArea::vertical().auto_shrink([false, true]);
+                scroll_area.show_rows(ui, SKILL_BOX.height(), rows, |ui, range| {
+                    let search_range = get_or_return!(self.backend.get_item_range(
+                        Some(slot.kind()),
+                        &self.search_query,
+                        &range
+                    ));
+                    for it in search_range {
+                        let is_selected = item.as_ref() == Some(it);
+                        let (e, b) =
+                            show_selectable_block(ui, &it.image, item_description(it), is_selected);
+                        if e.clicked() {
+                            edit_item = Some(Some(it.clone()));
+                            return;
+                        } else if b.clicked() {
+                            let mut backpack = hero.backpack.clone();
+                            match backpack.equip(slot, it.clone()) {
+                                Ok(()) => new_backpack = Some(backpack),
+                                Err(e) => self.backend_messages.push(e),
+                            }
+                        }
+                    }
+                });
             });
+        }
 
-            if let Some(skill) = skill {
-                ui.allocate_ui_at_rect(SKILL_TEXT.translate(offset), |ui| {
-                    let spec_top_label = egui::Label::new(&skill.name);
-                    ui.add(spec_top_label);
-                    ui.add_space(4.);
-                    let spec_bottom_label = egui::Label::new(&format!("{} ступени", skill.level));
-                    ui.add(spec_bottom_label);
+        let items = &hero.backpack.items;
+        let max_offset = (items.len() + 1).saturating_sub(BACKPACK_VISIBLE_ITEMS);
+        self.backpack_offset = self.backpack_offset.min(max_offset);
+        let left_button = egui::Button::new("◀").fill(egui::Color32::TRANSPARENT);
+        if ui.put(BACKPACK_LEFT, left_button).clicked() {
+            self.backpack_offset = self.backpack_offset.saturating_sub(1);
+        }
+        let right_button = egui::Button::new("▶").fill(egui::Color32::TRANSPARENT);
+        if ui.put(BACKPACK_RIGHT, right_button).clicked() {
+            self.backpack_offset = (self.backpack_offset + 1).min(max_offset);
+        }
+
+        for i in 0..BACKPACK_VISIBLE_ITEMS {
+            let idx = self.backpack_offset + i;
+            let rect = BACKPACK_ITEM.translate(BACKPACK_OFFSET * i as f32);
+            if let Some(item) = items.get(idx) {
+                let hover_text =
+                    format!("{}\nЛКМ — надеть, ПКМ — выбросить", item_description(item));
+                let widget_response = ui
+                    .put(rect, item.image.image_button())
+                    .on_hover_text(hover_text);
+                if widget_response.clicked() {
+                    let mut backpack = hero.backpack.clone();
+                    backpack.equip_from_backpack(idx);
+                    new_backpack = Some(backpack);
+                } else if widget_response.secondary_clicked() {
+                    let mut backpack = hero.backpack.clone();
+                    backpack.items.remove(idx);
+                    new_backpack = Some(backpack);
+                }
+            } else if idx == items.len() {
+                let button = egui::Button::new("").fill(egui::Color32::TRANSPARENT);
+                let widget_response = ui.put(rect, button).on_hover_text("Положить в рюкзак");
+                show_selection_window(ui, widget_response, "Рюкзак", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.search_query);
+                        if ui.button("➕").clicked() {
+                            edit_item = Some(None);
+                        }
+                    });
+
+                    let rows =
+                        get_or_return!(self.backend.get_item_row_count(None, &self.search_query));
+                    let scroll_area = egui::ScrollArea::vertical().auto_shrink([false, true]);
+                    scroll_area.show_rows(ui, SKILL_BOX.height(), rows, |ui, range| {
+                        let search_range = get_or_return!(self.backend.get_item_range(
+                            None,
+                            &self.search_query,
+                            &range
+                        ));
+                        for it in search_range {
+                            let (e, b) =
+                                show_selectable_block(ui, &it.image, item_description(it), false);
+                            if e.clicked() {
+                                edit_item = Some(Some(it.clone()));
+                                return;
+                            } else if b.clicked() {
+                                let mut backpack = hero.backpack.clone();
+                                backpack.items.push(it.clone());
+                                new_backpack = Some(backpack);
+                            }
+                        }
+                    });
+                });
+            }
+        }
+
+        if let Some(backpack) = new_backpack {
+            match self.backend.set_hero_backpack(hero.id, &backpack) {
+                Ok(()) => {
+                    hero.backpack = backpack;
+                    clamp_auto_mana(hero);
+                }
+                Err(e) => self.backend_messages.push(e),
+            }
+        }
+        show_item_edit_window(ui, edit_item, self.backend.as_mut(), frame);
+    }
+
+    fn show_spellbook(&mut self, ui: &mut egui::Ui, frame: &mut epi::Frame) {
+        let hero = get_or_return!(&mut self.hero);
+
+        let button = egui::Button::new("📖").fill(egui::Color32::TRANSPARENT);
+        let button_response = ui
+            .put(SPELLBOOK_BUTTON, button)
+            .on_hover_text("Книга заклинаний");
+
+        let mut new_spell = None;
+        let mut edit_spell = None;
+        show_selection_window(
+            ui,
+            button_response,
+            "Книга заклинаний",
+            |ui| {
+                ui.horizontal(|ui| {
+                    for school in std::iter::once(None).chain(SpellSchool::ALL.map(Some)) {
+                        let title = school.map_or("Все", SpellSchool::name);
+                        let tab = ui.selectable_label(self.spellbook_school == school, title);
+                        if tab.clicked() {
+                            self.spellbook_school = school;
+                            self.spellbook_page = 0;
+                        }
+                    }
                 });
+                ui.separator();
+
+                let school = self.spellbook_school;
+                let spells = hero
+                    .spellbook
+                    .iter()
+                    .filter(|s| school.map_or(true, |school| s.school == school))
+                    .collect::<Vec<_>>();
+                let pages = (spells.len().max(1) + SPELLBOOK_PAGE_SIZE - 1) / SPELLBOOK_PAGE_SIZE;
+                self.spellbook_page = self.spellbook_page.min(pages - 1);
+                let page = spells
+                    .iter()
+                    .skip(self.spellbook_page * SPELLBOOK_PAGE_SIZE)
+                    .take(SPELLBOOK_PAGE_SIZE);
+                for spell in page {
+                    let cast = spell_cast(hero, spell);
+                    let text = format!("{}\n{} маны", spell.name, cast.cost);
+                    let response = show_selectable_block_no_edit(ui, &spell.image, text, false)
+                        .on_hover_text(spell_description(spell, &cast));
+                    if response.secondary_clicked() {
+                        new_spell = Some((spell.id, false));
+                    }
+                }
+                if spells.is_empty() {
+                    ui.label("Нет заклинаний");
+                }
+                ui.horizontal(|ui| {
+                    let back = egui::Button::new("◀");
+                    if ui.add_enabled(self.spellbook_page > 0, back).clicked() {
+                        self.spellbook_page -= 1;
+                    }
+                    ui.label(format!("{} / {}", self.spellbook_page + 1, pages));
+                    let forward = egui::Button::new("▶");
+                    if ui
+                        .add_enabled(self.spellbook_page + 1 < pages, forward)
+                        .clicked()
+                    {
+                        self.spellbook_page += 1;
+                    }
+                });
+
+                egui::CollapsingHeader::new("Выучить").show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.search_query);
+                        if ui.button("➕").clicked() {
+                            edit_spell = Some(None);
+                        }
+                    });
+
+                    let rows = get_or_return!(self
+                        .backend
+                        .get_spell_row_count(school, &self.search_query));
+                    let scroll_area = egui::ScrollArea::vertical().auto_shrink([false, true]);
+                    scroll_area.show_rows(ui, SKILL_BOX.height(), rows, |ui, range| {
+                        let search_range = get_or_return!(self.backend.get_spell_range(
+                            school,
+                            &self.search_query,
+                            &range
+                        ));
+                        for s in search_range {
+                            let known = hero.spellbook.contains(s);
+                            let (e, b) = show_selectable_block(ui, &s.image, &s.name, known);
+                            if e.clicked() {
+                                edit_spell = Some(Some(s.clone()));
+                            } else if b.clicked() {
+                                new_spell = Some((s.id, !known));
+                            }
+                        }
+                    });
+                });
+            },
+        );
+
+        if let Some((spell_id, learned)) = new_spell {
+            match self.backend.set_hero_spell(hero.id, spell_id, learned) {
+                Ok(()) => {
+                    if let Some(updated_hero) = self.backend.get_hero(hero.id) {
+                        *hero = updated_hero;
+                    }
+                }
+                Err(e) => self.backend_messages.push(e),
             }
         }
-        show_skill_edit_window(ui, edit_skill, &mut self.backend, frame);
+        show_spell_edit_window(ui, edit_spell, self.backend.as_mut(), frame);
     }
 
     fn show_luck_morale(&mut self, ui: &mut egui::Ui) {
@@ -369,51 +1220,50 @@ impl HeroViewer {
             "Ярость!",
         ];
 
+        let luck = stat_value(hero, Stat::Luck);
+        let luck_idx = sprite_index(luck.effective());
         let luck_button_response = ui
-            .put(
-                LUCK_IMAGE,
-                self.static_assets.luck[hero.luck as usize].image_button(),
-            )
-            .on_hover_text(luck_titles[hero.luck as usize]);
+            .put(LUCK_IMAGE, self.static_assets.luck[luck_idx].image_button())
+            .on_hover_text(format!("{}\n{}", luck_titles[luck_idx], luck.breakdown()));
         show_selection_window(ui, luck_button_response, "Удача", |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
-                for (i, title) in luck_titles.iter().enumerate() {
-                    let selected = i as u8 == hero.luck;
-                    if show_selectable_block_no_edit(
-                        ui,
-                        &self.static_assets.luck[i],
-                        *title,
-                        selected,
-                    )
-                    .clicked()
-                    {
-                        hero.luck = i as u8;
-                        self.backend.set_hero_luck(hero.id, i as u8);
+                for (value, title) in LUCK_MORALE_RANGE.zip(luck_titles) {
+                    let sprite = &self.static_assets.luck[sprite_index(value as i16)];
+                    let title = format!("{} ({:+})", title, value);
+                    let selected = value == hero.luck;
+                    if show_selectable_block_no_edit(ui, sprite, title, selected).clicked() {
+                        match self.backend.set_hero_luck(hero.id, value) {
+                            Ok(()) => hero.luck = value,
+                            Err(e) => self.backend_messages.push(e),
+                        }
                     }
                 }
             })
         });
 
+        let morale = stat_value(hero, Stat::Morale);
+        let morale_idx = sprite_index(morale.effective());
         let morale_button_response = ui
             .put(
                 MORALE_IMAGE,
-                self.static_assets.morale[hero.morale as usize].image_button(),
+                self.static_assets.morale[morale_idx].image_button(),
             )
-            .on_hover_text(morale_titles[hero.morale as usize]);
+            .on_hover_text(format!(
+                "{}\n{}",
+                morale_titles[morale_idx],
+                morale.breakdown()
+            ));
         show_selection_window(ui, morale_button_response, "Мораль", |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
-                for (i, title) in morale_titles.iter().enumerate() {
-                    let selected = i as u8 == hero.morale;
-                    if show_selectable_block_no_edit(
-                        ui,
-                        &self.static_assets.morale[i],
-                        *title,
-                        selected,
-                    )
-                    .clicked()
-                    {
-                        hero.morale = i as u8;
-                        self.backend.set_hero_morale(hero.id, i as u8);
+                for (value, title) in LUCK_MORALE_RANGE.zip(morale_titles) {
+                    let sprite = &self.static_assets.morale[sprite_index(value as i16)];
+                    let title = format!("{} ({:+})", title, value);
+                    let selected = value == hero.morale;
+                    if show_selectable_block_no_edit(ui, sprite, title, selected).clicked() {
+                        match self.backend.set_hero_morale(hero.id, value) {
+                            Ok(()) => hero.morale = value,
+                            Err(e) => self.backend_messages.push(e),
+                        }
                     }
                 }
             })
@@ -423,14 +1273,61 @@ impl HeroViewer {
     fn show_settings(&mut self, ui: &mut egui::Ui) {
         use egui::*;
         use BackendStatus::*;
-        let button_response = ui.put(FLAG_IMAGE, self.static_assets.flag.image_button());
+        let players = self.backend.get_players().unwrap_or_default();
+        if !players.iter().any(|p| p.id == self.player_id) {
+            if let Some(first) = players.first() {
+                self.player_id = first.id;
+                self.set_hero(None);
+                self.selected_hero_idx = 0;
+            }
+        }
+        let current_player = players.iter().find(|p| p.id == self.player_id);
+        let crest = self
+            .static_assets
+            .flag
+            .image_button()
+            .tint(current_player.map_or(Color32::WHITE, Player::color32));
+        let button_response = ui.put(FLAG_IMAGE, crest);
+        let mut edit_player = None;
         show_selection_window(ui, button_response, "Настройки", |ui| {
             let status = self.backend.get_status();
             Grid::new("grid").num_columns(2).show(ui, |ui| {
+                let config = &mut self.db_config;
+                let editable = status == NotConnected;
+                ui.label("Хост");
+                ui.add_enabled(editable, TextEdit::singleline(&mut config.host));
+                ui.end_row();
+
+                ui.label("Порт");
+                ui.add_enabled(editable, DragValue::new(&mut config.port));
+                ui.end_row();
+
+                ui.label("Пользователь");
+                ui.add_enabled(editable, TextEdit::singleline(&mut config.user));
+                ui.end_row();
+
+                ui.label("Пароль");
+                ui.add_enabled(
+                    editable,
+                    TextEdit::singleline(&mut config.password).password(true),
+                );
+                ui.end_row();
+
+                ui.label("База данных");
+                ui.add_enabled(editable, TextEdit::singleline(&mut config.database));
+                ui.end_row();
+
+                ui.label("Соединений");
+                ui.add_enabled(
+                    editable,
+                    DragValue::new(&mut config.pool_size).clamp_range(1..=32),
+                );
+                ui.end_row();
+
                 ui.label("Подключиться к БД");
                 let connect_button = ui.add_enabled(status == NotConnected, Button::new("Connect"));
                 if connect_button.clicked() {
-                    self.backend.connect_to_db();
+                    self.backend.connect_to_db(&self.db_config);
                 }
                 ui.end_row();
 
@@ -438,7 +1335,7 @@ impl HeroViewer {
                 let create_db_button =
                     ui.add_enabled(status == NotConnected, Button::new("Create DB"));
                 if create_db_button.clicked() {
-                    self.backend.create_db();
+                    self.backend.create_db(&self.db_config);
                 }
                 ui.end_row();
 
@@ -448,15 +1345,70 @@ impl HeroViewer {
                     Button::new("Drop DB"),
                 );
                 if drop_db_button.clicked() {
-                    self.backend.drop_db();
+                    self.backend.drop_db(&self.db_config);
                 }
                 ui.end_row();
 
                 ui.label("Размер интерфейса");
                 ui.add(Slider::new(&mut self.pixels_per_point, 1.0..=1.5));
-                ui.end_row()
+                ui.end_row();
+
+                if status == NotConnected || status == Connecting {
+                    return;
+                }
+
+                ui.label("Игрок");
+                ui.horizontal(|ui| {
+                    let previous_player_id = self.player_id;
+                    ComboBox::from_id_source("player")
+                        .selected_text(current_player.map_or("", |p| p.name.as_str()))
+                        .show_ui(ui, |ui| {
+                            for player in &players {
+                                ui.selectable_value(&mut self.player_id, player.id, &player.name);
+                            }
+                        });
+                    if self.player_id != previous_player_id {
+                        self.set_hero(None);
+                        self.selected_hero_idx = 0;
+                    }
+                    if let Some(player) = current_player {
+                        if ui.small_button("📝").clicked() {
+                            edit_player = Some(Some(player.clone()));
+                        }
+                    }
+                    if ui.small_button("➕").clicked() {
+                        edit_player = Some(None);
+                    }
+                });
+                ui.end_row();
+
+                if let Some(hero) = &self.hero {
+                    let hero_id = hero.id;
+                    let mut owner_id = hero.player_id;
+                    let owner = players.iter().find(|p| p.id == owner_id);
+                    ui.label("Владелец героя");
+                    ComboBox::from_id_source("owner")
+                        .selected_text(owner.map_or("", |p| p.name.as_str()))
+                        .show_ui(ui, |ui| {
+                            for player in &players {
+                                ui.selectable_value(&mut owner_id, player.id, &player.name);
+                            }
+                        });
+                    if owner_id != hero.player_id {
+                        match self.backend.set_hero_player(hero_id, owner_id) {
+                            // The hero is no longer in this player's switcher.
+                            Ok(()) => {
+                                self.set_hero(None);
+                                self.selected_hero_idx = 0;
+                            }
+                            Err(e) => self.backend_messages.push(e),
+                        }
+                    }
+                    ui.end_row();
+                }
             });
-        })
+        });
+        show_player_edit_window(ui, edit_player, self.backend.as_mut());
     }
 
     fn process_global_hotkeys(&mut self, input: &egui::InputState) {
@@ -467,3 +1419,29 @@ impl HeroViewer {
         }
     }
 }
+
+fn item_description(item: &Item) -> String {
+    let bonuses = Stat::ALL
+        .iter()
+        .zip(item.bonuses)
+        .filter(|(_, bonus)| *bonus != 0)
+        .map(|(stat, bonus)| format!("\n{:+} {}", bonus, stat.name()))
+        .collect::<String>();
+    format!("{}\n{}{}", item.name, item.kind.name(), bonuses)
+}
+
+fn spell_description(spell: &Spell, cast: &SpellCast) -> String {
+    let mastery = cast.mastery.map_or("нет", |m| m.name());
+    format!(
+        "{}\n{}, {}-й уровень\n{}: {}\nСтоимость: ≈{} (без навыка {})\nСила: ≈{}\n\
+         (приблизительно, не по таблицам игры)\nПравый клик — стереть",
+        spell.name,
+        spell.school.name(),
+        spell.level,
+        spell.school.skill_name(),
+        mastery,
+        cast.cost,
+        spell.cost,
+        cast.effect
+    )
+}
@@ -0,0 +1,142 @@
+use crate::experience::{experience_for_level, MAX_LEVEL};
+use crate::hero::Hero;
+use crate::hero_class::HeroClass;
+
+/// Small seedable generator (SplitMix64), so that a seed always replays the
+/// same build on every platform, wasm included.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        (((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+    }
+
+    /// Index into `weights`, picked with probability proportional to its weight.
+    pub fn weighted(&mut self, weights: &[u32]) -> usize {
+        let mut roll = self.below(weights.iter().sum());
+        for (i, weight) in weights.iter().enumerate() {
+            if roll < *weight {
+                return i;
+            }
+            roll -= weight;
+        }
+        weights.len() - 1
+    }
+}
+
+/// Used for heroes whose class is missing from the catalogue.
+const DEFAULT_GROWTH: [u8; 4] = [25, 25, 25, 25];
+
+/// Picks the primary skill (an index into `Hero::pskills`) that a hero of
+/// `class` raises on reaching `new_level`.
+pub fn roll_pskill(class: Option<&HeroClass>, new_level: u8, rng: &mut Rng) -> usize {
+    let growth = class
+        .map(|c| {
+            if new_level < 10 {
+                c.low_level_growth
+            } else {
+                c.high_level_growth
+            }
+        })
+        .unwrap_or(DEFAULT_GROWTH);
+    rng.weighted(&growth.map(u32::from))
+}
+
+/// Takes `hero` (of `class`) to the next level: sets the experience to that
+/// level's threshold and raises one primary skill. Returns the index of that
+/// skill, or `None` if the hero is already at `MAX_LEVEL`.
+pub fn level_up(hero: &mut Hero, class: Option<&HeroClass>, rng: &mut Rng) -> Option<usize> {
+    if hero.level >= MAX_LEVEL {
+        return None;
+    }
+    hero.level += 1;
+    hero.experience = experience_for_level(hero.level);
+    let pskill = roll_pskill(class, hero.level, rng);
+    hero.pskills[pskill] = hero.pskills[pskill].saturating_add(1);
+    Some(pskill)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class_growing(low_level: usize, high_level: usize) -> HeroClass {
+        let mut class = HeroClass::default();
+        class.low_level_growth[low_level] = 100;
+        class.high_level_growth[high_level] = 100;
+        class
+    }
+
+    #[test]
+    fn rng_is_splitmix64() {
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn below_scales_the_high_bits() {
+        let mut rng = Rng::new(0);
+        let rolls = (0..5).map(|_| rng.below(100)).collect::<Vec<_>>();
+        assert_eq!(rolls, [88, 43, 2, 97, 10]);
+    }
+
+    #[test]
+    fn weighted_skips_zero_weights() {
+        let mut rng = Rng::new(0);
+        for _ in 0..100 {
+            assert_eq!(rng.weighted(&[0, 0, 5, 0]), 2);
+        }
+    }
+
+    #[test]
+    fn roll_pskill_uses_the_growth_of_the_level_range() {
+        let class = class_growing(1, 3);
+        let mut rng = Rng::new(0);
+        assert_eq!(roll_pskill(Some(&class), 9, &mut rng), 1);
+        assert_eq!(roll_pskill(Some(&class), 10, &mut rng), 3);
+        // 88 out of the default 25/25/25/25 lands on knowledge.
+        assert_eq!(roll_pskill(None, 2, &mut Rng::new(0)), 3);
+    }
+
+    #[test]
+    fn level_up_moves_to_the_next_threshold() {
+        let mut hero = Hero {
+            pskills: [1, 1, 1, 1],
+            level: 1,
+            ..Hero::default()
+        };
+        let class = class_growing(0, 0);
+        assert_eq!(level_up(&mut hero, Some(&class), &mut Rng::new(0)), Some(0));
+        assert_eq!(hero.level, 2);
+        assert_eq!(hero.experience, 1000);
+        assert_eq!(hero.pskills, [2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn no_level_up_past_max_level() {
+        let mut hero = Hero {
+            level: MAX_LEVEL,
+            experience: experience_for_level(MAX_LEVEL),
+            ..Hero::default()
+        };
+        assert_eq!(level_up(&mut hero, None, &mut Rng::new(0)), None);
+        assert_eq!(hero.level, MAX_LEVEL);
+    }
+}
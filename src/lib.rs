@@ -1,18 +1,51 @@
 mod backend;
-// mod backpack;
+mod backpack;
 mod character;
+mod config;
+mod db;
+mod experience;
 mod geometry;
 mod hero;
+mod hero_class;
 mod hero_viewer;
+mod level_up;
+mod player;
+mod search;
+mod secondary_stats;
 mod skill;
+mod skill_offers;
 mod spec;
-// mod unit;
+mod spell;
 mod static_assets;
+mod stats;
+mod unit;
 mod utils;
+mod validation;
 mod widgets;
 
+pub use backend::{BackendStatus, DemoBackend, HeroBackend};
+pub use backpack::{Backpack, Item, ItemKind, ItemSlot};
+pub use character::Character;
+pub use config::DbConfig;
+pub use experience::{experience_for_level, level_for_experience, MAX_LEVEL};
 pub use geometry::WINDOW_SIZE;
+pub use hero::{Hero, HeroSelectButton};
+pub use hero_class::{ClassKind, HeroClass};
 pub use hero_viewer::HeroViewer;
+pub use level_up::{level_up, roll_pskill, Rng};
+pub use player::Player;
+pub use secondary_stats::{secondary_stats, SecondaryStats};
+pub use skill::{Mastery, Skill, SkillKind};
+pub use skill_offers::{skill_offers, OfferStreaks, SkillOffer};
+pub use spec::Spec;
+pub use spell::{spell_cast, Spell, SpellCast, SpellSchool};
+pub use stats::{
+    auto_mana_max, clamp_auto_mana, mana_max, sprite_index, stat_value, Bonuses, Stat, StatValue,
+    LUCK_MORALE_RANGE,
+};
+pub use unit::{Creature, Unit};
+pub use utils::RawImage;
+pub use validation::{check_change, validate, Violation};
 
 #[cfg(target_arch = "wasm32")]
 use eframe::wasm_bindgen::{self, prelude::*};
@@ -0,0 +1,22 @@
+use eframe::egui::Color32;
+
+#[derive(Clone, Debug, Default)]
+pub struct Player {
+    pub id: usize,
+    pub name: String,
+    /// Red, green and blue of the player's crest.
+    pub color: [u8; 3],
+}
+
+impl Player {
+    pub fn color32(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+impl PartialEq for Player {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
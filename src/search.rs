@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Rows fetched by a single `LIMIT/OFFSET` query.
+pub const PAGE_SIZE: usize = 32;
+
+/// Searches kept at once; the least recently requested one makes room.
+const MAX_SEARCHES: usize = 16;
+
+/// Scope of a search (e.g. the hero class for specialties) and the text typed
+/// into the search box.
+pub type SearchKey = (String, String);
+
+pub enum SearchReply<R> {
+    Count(u64, SearchKey, usize),
+    Page(u64, SearchKey, usize, Vec<R>),
+}
+
+struct Search<T> {
+    count: Option<usize>,
+    count_requested: bool,
+    pages: HashMap<usize, Vec<T>>,
+    pages_requested: HashSet<usize>,
+    last_used: u64,
+}
+
+impl<T> Default for Search<T> {
+    fn default() -> Self {
+        Self {
+            count: None,
+            count_requested: false,
+            pages: HashMap::new(),
+            pages_requested: HashSet::new(),
+            last_used: 0,
+        }
+    }
+}
+
+/// Per-range cache behind the virtual-scrolled selection windows.
+///
+/// Counts and pages are requested lazily and filled in as replies arrive.
+/// Replies carry the generation they were requested in, so anything that
+/// was in flight while the cache got cleared is dropped instead of
+/// resurrecting stale rows. Only the [`MAX_SEARCHES`] most recently
+/// requested searches are kept.
+pub struct SearchCache<T> {
+    generation: u64,
+    searches: HashMap<SearchKey, Search<T>>,
+    /// Bumped on every request, to tell which search was used last.
+    clock: u64,
+}
+
+impl<T> Default for SearchCache<T> {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            searches: HashMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<T> SearchCache<T> {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn clear(&mut self) {
+        self.generation += 1;
+        self.searches.clear();
+    }
+
+    pub fn count(&self, key: &SearchKey) -> Option<usize> {
+        self.searches.get(key).and_then(|s| s.count)
+    }
+
+    /// Marks the count as requested. Returns `false` if it already was.
+    pub fn request_count(&mut self, key: &SearchKey) -> bool {
+        let search = self.touch(key);
+        !std::mem::replace(&mut search.count_requested, true)
+    }
+
+    /// Marks every page overlapping `range` as requested and returns the ones
+    /// that weren't yet.
+    pub fn request_pages(&mut self, key: &SearchKey, range: &Range<usize>) -> Vec<usize> {
+        let search = self.touch(key);
+        pages_of(range)
+            .filter(|page| search.pages_requested.insert(*page))
+            .collect()
+    }
+
+    /// Rows in `range`, or `None` while any of their pages is still loading.
+    pub fn range<'a>(
+        &'a self,
+        key: &SearchKey,
+        range: &Range<usize>,
+    ) -> Option<impl Iterator<Item = &'a T>> {
+        let search = self.searches.get(key)?;
+        let pages = pages_of(range)
+            .map(|page| search.pages.get(&page).map(|rows| rows.iter()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(
+            pages
+                .into_iter()
+                .flatten()
+                .skip(range.start % PAGE_SIZE)
+                .take(range.len()),
+        )
+    }
+
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<&T> {
+        self.searches
+            .values()
+            .flat_map(|s| s.pages.values())
+            .flatten()
+            .find(|row| predicate(row))
+    }
+
+    /// Replies to searches that were evicted in the meantime are dropped.
+    pub fn apply<R>(&mut self, reply: SearchReply<R>, convert: impl FnMut(R) -> T) {
+        match reply {
+            SearchReply::Count(generation, key, count) if generation == self.generation => {
+                if let Some(search) = self.searches.get_mut(&key) {
+                    search.count = Some(count);
+                }
+            }
+            SearchReply::Page(generation, key, page, rows) if generation == self.generation => {
+                if let Some(search) = self.searches.get_mut(&key) {
+                    search
+                        .pages
+                        .insert(page, rows.into_iter().map(convert).collect());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The search for `key`, marked as the most recently used one. A new
+    /// search pushes out the least recently used one once the cache is full.
+    fn touch(&mut self, key: &SearchKey) -> &mut Search<T> {
+        self.clock += 1;
+        if !self.searches.contains_key(key) && self.searches.len() >= MAX_SEARCHES {
+            let oldest = self
+                .searches
+                .iter()
+                .min_by_key(|(_, s)| s.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.searches.remove(&oldest);
+            }
+        }
+        let search = self.searches.entry(key.clone()).or_default();
+        search.last_used = self.clock;
+        search
+    }
+}
+
+fn pages_of(range: &Range<usize>) -> Range<usize> {
+    if range.is_empty() {
+        return 0..0;
+    }
+    range.start / PAGE_SIZE..(range.end - 1) / PAGE_SIZE + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(i: usize) -> SearchKey {
+        (String::new(), i.to_string())
+    }
+
+    #[test]
+    fn least_recently_requested_search_is_evicted() {
+        let mut cache = SearchCache::<u32>::default();
+        for i in 0..MAX_SEARCHES {
+            assert!(cache.request_count(&key(i)));
+        }
+        cache.request_pages(&key(0), &(0..1));
+        cache.request_count(&key(MAX_SEARCHES));
+
+        assert!(!cache.request_count(&key(0)));
+        assert!(cache.request_count(&key(1)));
+    }
+
+    #[test]
+    fn replies_for_evicted_searches_are_dropped() {
+        let mut cache = SearchCache::<u32>::default();
+        cache.request_count(&key(0));
+        for i in 1..=MAX_SEARCHES {
+            cache.request_count(&key(i));
+        }
+        cache.apply(SearchReply::Count(0, key(0), 5), |r: u32| r);
+        cache.apply(SearchReply::Page(0, key(1), 0, vec![7]), |r: u32| r);
+
+        assert_eq!(cache.count(&key(0)), None);
+        assert_eq!(cache.range(&key(1), &(0..1)).unwrap().next(), Some(&7));
+    }
+}
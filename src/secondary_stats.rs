@@ -0,0 +1,112 @@
+use crate::hero::Hero;
+
+/// Secondary skills with what each mastery gives.
+const LOGISTICS: (&str, [u32; 3]) = ("logistics", [10, 20, 30]);
+const PATHFINDING: (&str, [u8; 3]) = ("pathfinding", [25, 50, 75]);
+const SCOUTING: (&str, [u8; 3]) = ("scouting", [1, 2, 3]);
+const MYSTICISM: (&str, [u16; 3]) = ("mysticism", [2, 3, 4]);
+
+/// Land movement points by the speed of the slowest unit, from 3 and below
+/// up to 11 and above.
+const MOVEMENT_BY_SPEED: [u32; 9] = [1500, 1560, 1630, 1700, 1760, 1830, 1900, 1960, 2000];
+const BASE_SCOUTING_RADIUS: u8 = 5;
+const BASE_MANA_REGEN: u16 = 1;
+
+/// Numbers of the adventure map that follow from the hero's skills and army.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecondaryStats {
+    /// Speed of the slowest unit, `None` for an empty army.
+    pub army_speed: Option<u8>,
+    /// Movement points the army gives before Logistics.
+    pub base_movement: u32,
+    pub movement: u32,
+    /// Mana restored each day.
+    pub mana_regen: u16,
+    pub scouting_radius: u8,
+    /// Percent of the rough terrain penalty that Pathfinding takes away.
+    pub terrain_penalty_cut: u8,
+}
+
+pub fn secondary_stats(hero: &Hero) -> SecondaryStats {
+    let army_speed = hero.units.iter().flatten().map(|u| u.creature.speed).min();
+    let speed_idx = army_speed.map_or(0, |speed| speed.saturating_sub(3) as usize);
+    let base_movement = MOVEMENT_BY_SPEED[speed_idx.min(MOVEMENT_BY_SPEED.len() - 1)];
+
+    SecondaryStats {
+        army_speed,
+        base_movement,
+        movement: base_movement * (100 + skill_bonus(hero, LOGISTICS)) / 100,
+        mana_regen: BASE_MANA_REGEN + skill_bonus(hero, MYSTICISM),
+        scouting_radius: BASE_SCOUTING_RADIUS + skill_bonus(hero, SCOUTING),
+        terrain_penalty_cut: skill_bonus(hero, PATHFINDING),
+    }
+}
+
+fn skill_bonus<T: Copy + Default>(hero: &Hero, (code, bonuses): (&str, [T; 3])) -> T {
+    hero.skills
+        .iter()
+        .flatten()
+        .find(|s| s.kind.is(code))
+        .map_or_else(T::default, |s| bonuses[s.mastery.level() as usize - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::{test_skill, Mastery};
+    use crate::unit::{Creature, Unit};
+
+    fn unit(speed: u8) -> Option<Unit> {
+        Some(Unit {
+            creature: Creature {
+                speed,
+                ..Creature::default()
+            },
+            count: 1,
+        })
+    }
+
+    #[test]
+    fn empty_hero_gets_the_base_values() {
+        assert_eq!(
+            secondary_stats(&Hero::default()),
+            SecondaryStats {
+                army_speed: None,
+                base_movement: 1500,
+                movement: 1500,
+                mana_regen: 1,
+                scouting_radius: 5,
+                terrain_penalty_cut: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn slowest_unit_and_skills_set_the_numbers() {
+        let mut hero = Hero::default();
+        hero.units[0] = unit(7);
+        hero.units[4] = unit(5);
+        hero.skills[0] = Some(test_skill(1, "logistics", Mastery::Expert));
+        hero.skills[1] = Some(test_skill(2, "mysticism", Mastery::Advanced));
+        hero.skills[2] = Some(test_skill(3, "scouting", Mastery::Basic));
+        hero.skills[3] = Some(test_skill(4, "pathfinding", Mastery::Expert));
+        assert_eq!(
+            secondary_stats(&hero),
+            SecondaryStats {
+                army_speed: Some(5),
+                base_movement: 1630,
+                movement: 2119,
+                mana_regen: 4,
+                scouting_radius: 6,
+                terrain_penalty_cut: 75,
+            }
+        );
+    }
+
+    #[test]
+    fn fast_armies_top_out() {
+        let mut hero = Hero::default();
+        hero.units[0] = unit(20);
+        assert_eq!(secondary_stats(&hero).base_movement, 2000);
+    }
+}
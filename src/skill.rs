@@ -1,55 +1,96 @@
 use crate::utils::RawImage;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mastery {
+    Basic,
+    Advanced,
+    Expert,
+}
+
+impl Default for Mastery {
+    fn default() -> Self {
+        Mastery::Basic
+    }
+}
+
+impl Mastery {
+    pub const ALL: [Mastery; 3] = [Mastery::Basic, Mastery::Advanced, Mastery::Expert];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mastery::Basic => "Базовый",
+            Mastery::Advanced => "Продвинутый",
+            Mastery::Expert => "Эксперт",
+        }
+    }
+
+    /// 1 for `Basic` up to 3 for `Expert`, as stored in the database.
+    pub fn level(self) -> u8 {
+        self as u8 + 1
+    }
+
+    pub fn from_level(level: u8) -> Mastery {
+        Mastery::ALL[(level.clamp(1, 3) - 1) as usize]
+    }
+
+    pub fn next(self) -> Option<Mastery> {
+        Mastery::ALL.get(self as usize + 1).copied()
+    }
+
+    pub fn prev(self) -> Option<Mastery> {
+        (self as usize).checked_sub(1).map(|i| Mastery::ALL[i])
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkillKind {
+    pub id: usize,
+    pub name: String,
+    /// Stable name the game's rules know the kind by, e.g. `luck`; `None` for
+    /// kinds no rule cares about.
+    pub code: Option<String>,
+}
+
+impl SkillKind {
+    pub fn is(&self, code: &str) -> bool {
+        self.code.as_deref() == Some(code)
+    }
+}
+
+/// A skill kind at one mastery, with the picture for that mastery.
 #[derive(Clone, Debug, Default)]
 pub struct Skill {
     pub id: usize,
-    pub name: String,
-    pub level: u8,
+    pub kind: SkillKind,
+    pub mastery: Mastery,
     pub image: RawImage,
 }
+
+impl Skill {
+    /// `Удача` on one line and `Эксперт` on the next.
+    pub fn title(&self) -> String {
+        format!("{}\n{}", self.kind.name, self.mastery.name())
+    }
+}
+
 impl PartialEq for Skill {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
     }
 }
 
-pub fn demo_skills(frame: &mut eframe::epi::Frame<'_>) -> Vec<Skill> {
-    vec![
-        Skill {
-            name: "Некромантия".to_string(),
-            level: 3,
-            id: 0,
-            image: RawImage::from_bytes(include_bytes!("../resources/skill/necro_3.png"), frame),
-        },
-        Skill {
-            name: "Торговля".to_string(),
-            level: 1,
-            id: 1,
-            image: RawImage::from_bytes(include_bytes!("../resources/skill/merchant_1.png"), frame),
-        },
-        Skill {
-            name: "Нападение".to_string(),
-            level: 1,
-            id: 2,
-            image: RawImage::from_bytes(include_bytes!("../resources/skill/offence_1.png"), frame),
+/// A skill of kind `kind_id` with an id that differs per mastery, named and
+/// coded `code`.
+#[cfg(test)]
+pub(crate) fn test_skill(kind_id: usize, code: &str, mastery: Mastery) -> Skill {
+    Skill {
+        id: kind_id * 10 + mastery.level() as usize,
+        kind: SkillKind {
+            id: kind_id,
+            name: code.to_string(),
+            code: Some(code.to_string()),
         },
-        Skill {
-            name: "Удача".to_string(),
-            level: 1,
-            id: 3,
-            image: RawImage::from_bytes(include_bytes!("../resources/skill/luck_1.png"), frame),
-        },
-        Skill {
-            name: "Удача".to_string(),
-            level: 2,
-            id: 4,
-            image: RawImage::from_bytes(include_bytes!("../resources/skill/luck_2.png"), frame),
-        },
-        Skill {
-            name: "Удача".to_string(),
-            level: 3,
-            id: 5,
-            image: RawImage::from_bytes(include_bytes!("../resources/skill/luck_3.png"), frame),
-        },
-    ]
+        mastery,
+        image: Default::default(),
+    }
 }
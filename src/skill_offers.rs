@@ -0,0 +1,259 @@
+use crate::hero_class::HeroClass;
+use crate::level_up::Rng;
+use crate::skill::{Mastery, Skill, SkillKind};
+use crate::spell::SpellSchool;
+
+const WISDOM: &str = "wisdom";
+/// Wisdom is offered on the `WISDOM_EVERY`-th level-up in a row without it,
+/// while it can still be learned or upgraded; the same goes for the magic
+/// schools with `MAGIC_SCHOOL_EVERY`.
+const WISDOM_EVERY: u8 = 6;
+const MAGIC_SCHOOL_EVERY: u8 = 4;
+
+/// Weight of the skills missing from [`HeroClass::skill_weights`]. The game's
+/// per-class table isn't shipped, so no class has weights until they are
+/// entered in the class picker.
+pub const DEFAULT_SKILL_WEIGHT: u32 = 3;
+
+/// Level-ups in a row a hero went without being offered Wisdom and without
+/// being offered a magic school.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OfferStreaks {
+    pub without_wisdom: u8,
+    pub without_magic_school: u8,
+}
+
+/// A skill offered on level-up and the hero skill slot it goes into.
+#[derive(Clone, Debug)]
+pub struct SkillOffer {
+    pub slot: usize,
+    pub skill: Skill,
+}
+
+fn skill_weight(class: Option<&HeroClass>, kind: &SkillKind) -> u32 {
+    class
+        .and_then(|c| c.skill_weights.iter().find(|(k, _)| k.id == kind.id))
+        .map(|(_, weight)| *weight)
+        .unwrap_or(DEFAULT_SKILL_WEIGHT)
+}
+
+/// Offers the game makes to a hero of `class` on level-up, picked from
+/// `catalogue`: an upgrade of a skill the hero has and a new skill. When one
+/// kind is not available, both offers are of the other kind; when neither is,
+/// there are no offers. Wisdom and magic schools take precedence once the
+/// hero has gone long enough without them; `streaks` is updated with what was
+/// offered this time.
+pub fn skill_offers(
+    skills: &[Option<Skill>; 8],
+    catalogue: &[Skill],
+    class: Option<&HeroClass>,
+    streaks: &mut OfferStreaks,
+    rng: &mut Rng,
+) -> Vec<SkillOffer> {
+    let upgrades = skills
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, skill)| {
+            let skill = skill.as_ref()?;
+            let mastery = skill.mastery.next()?;
+            let upgraded = catalogue
+                .iter()
+                .find(|s| s.kind == skill.kind && s.mastery == mastery)?;
+            Some(SkillOffer {
+                slot,
+                skill: upgraded.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let new_skills = match skills.iter().position(Option::is_none) {
+        Some(slot) => catalogue
+            .iter()
+            .filter(|s| s.mastery == Mastery::Basic)
+            .filter(|s| !skills.iter().flatten().any(|s2| s2.kind == s.kind))
+            .map(|s| SkillOffer {
+                slot,
+                skill: s.clone(),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let magic_schools = SpellSchool::ALL.map(SpellSchool::skill_code);
+    let mut preferred = Vec::new();
+    if streaks.without_wisdom + 1 >= WISDOM_EVERY {
+        preferred.push(WISDOM);
+    }
+    if streaks.without_magic_school + 1 >= MAGIC_SCHOOL_EVERY {
+        preferred.extend(magic_schools);
+    }
+
+    let mut offers = Vec::new();
+    let first = pick(&upgrades, class, &preferred, &offers, rng)
+        .or_else(|| pick(&new_skills, class, &preferred, &offers, rng));
+    offers.extend(first);
+    let second = pick(&new_skills, class, &preferred, &offers, rng)
+        .or_else(|| pick(&upgrades, class, &preferred, &offers, rng));
+    offers.extend(second);
+
+    let offered = |codes: &[&str]| {
+        offers
+            .iter()
+            .any(|o| codes.iter().any(|code| o.skill.kind.is(code)))
+    };
+    streaks.without_wisdom = if offered(&[WISDOM]) {
+        0
+    } else {
+        streaks.without_wisdom.saturating_add(1)
+    };
+    streaks.without_magic_school = if offered(&magic_schools) {
+        0
+    } else {
+        streaks.without_magic_school.saturating_add(1)
+    };
+    offers
+}
+
+/// Weighted pick among `candidates` not already `taken`, limited to the
+/// `preferred` skills if any of them is there.
+fn pick(
+    candidates: &[SkillOffer],
+    class: Option<&HeroClass>,
+    preferred: &[&str],
+    taken: &[SkillOffer],
+    rng: &mut Rng,
+) -> Option<SkillOffer> {
+    let candidates = candidates
+        .iter()
+        .filter(|c| !taken.iter().any(|t| t.skill.kind == c.skill.kind))
+        .collect::<Vec<_>>();
+    let preferred_candidates = candidates
+        .iter()
+        .filter(|c| preferred.iter().any(|code| c.skill.kind.is(code)))
+        .copied()
+        .collect::<Vec<_>>();
+    let pool = if preferred_candidates.is_empty() {
+        candidates
+    } else {
+        preferred_candidates
+    };
+    if pool.is_empty() {
+        return None;
+    }
+
+    let weights = pool
+        .iter()
+        .map(|c| skill_weight(class, &c.skill.kind))
+        .collect::<Vec<_>>();
+    Some(pool[rng.weighted(&weights)].clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::test_skill;
+
+    fn catalogue(codes: &[&str]) -> Vec<Skill> {
+        codes
+            .iter()
+            .enumerate()
+            .map(|(i, code)| test_skill(i + 1, code, Mastery::Basic))
+            .collect()
+    }
+
+    fn names(offers: &[SkillOffer]) -> Vec<&str> {
+        offers.iter().map(|o| o.skill.kind.name.as_str()).collect()
+    }
+
+    #[test]
+    fn offers_an_upgrade_and_a_new_skill() {
+        let mut skills: [Option<Skill>; 8] = Default::default();
+        skills[0] = Some(test_skill(1, "offence", Mastery::Basic));
+        let catalogue = [
+            test_skill(1, "offence", Mastery::Advanced),
+            test_skill(2, "logistics", Mastery::Basic),
+        ];
+        let mut streaks = OfferStreaks::default();
+        let offers = skill_offers(&skills, &catalogue, None, &mut streaks, &mut Rng::new(0));
+
+        assert_eq!(names(&offers), ["offence", "logistics"]);
+        assert_eq!(offers[0].slot, 0);
+        assert_eq!(offers[0].skill.mastery, Mastery::Advanced);
+        assert_eq!(offers[1].slot, 1);
+        assert_eq!(
+            streaks,
+            OfferStreaks {
+                without_wisdom: 1,
+                without_magic_school: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn nothing_to_offer_with_every_slot_at_expert() {
+        let skills = [1, 2, 3, 4, 5, 6, 7, 8].map(|k| Some(test_skill(k, "", Mastery::Expert)));
+        let mut streaks = OfferStreaks::default();
+        let offers = skill_offers(&skills, &[], None, &mut streaks, &mut Rng::new(0));
+        assert!(offers.is_empty());
+    }
+
+    #[test]
+    fn wisdom_comes_after_five_level_ups_without_it() {
+        let catalogue = catalogue(&["logistics", "scouting", WISDOM]);
+        for seed in 0..20 {
+            let mut streaks = OfferStreaks {
+                without_wisdom: WISDOM_EVERY - 1,
+                without_magic_school: 0,
+            };
+            let offers = skill_offers(
+                &Default::default(),
+                &catalogue,
+                None,
+                &mut streaks,
+                &mut Rng::new(seed),
+            );
+            assert_eq!(offers[0].skill.kind.name, WISDOM);
+            assert_eq!(streaks.without_wisdom, 0);
+            assert_eq!(streaks.without_magic_school, 1);
+        }
+    }
+
+    #[test]
+    fn magic_school_comes_after_three_level_ups_without_one() {
+        let catalogue = catalogue(&["logistics", "scouting", "fire_magic"]);
+        for seed in 0..20 {
+            let mut streaks = OfferStreaks {
+                without_wisdom: 0,
+                without_magic_school: MAGIC_SCHOOL_EVERY - 1,
+            };
+            let offers = skill_offers(
+                &Default::default(),
+                &catalogue,
+                None,
+                &mut streaks,
+                &mut Rng::new(seed),
+            );
+            assert_eq!(offers[0].skill.kind.name, "fire_magic");
+            assert_eq!(streaks.without_magic_school, 0);
+        }
+    }
+
+    #[test]
+    fn zero_weight_skills_come_last() {
+        let catalogue = catalogue(&["logistics", "scouting"]);
+        let class = HeroClass {
+            skill_weights: vec![(catalogue[0].kind.clone(), 0)],
+            ..HeroClass::default()
+        };
+        for seed in 0..20 {
+            let offers = skill_offers(
+                &Default::default(),
+                &catalogue,
+                Some(&class),
+                &mut OfferStreaks::default(),
+                &mut Rng::new(seed),
+            );
+            assert_eq!(names(&offers), ["scouting", "logistics"]);
+        }
+    }
+}
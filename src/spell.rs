@@ -0,0 +1,167 @@
+use std::ops::RangeInclusive;
+
+use crate::hero::Hero;
+use crate::skill::Mastery;
+use crate::stats::{stat_value, Stat};
+use crate::utils::RawImage;
+
+pub const SPELL_LEVELS: RangeInclusive<u8> = 1..=5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpellSchool {
+    Air,
+    Earth,
+    Fire,
+    Water,
+}
+
+impl Default for SpellSchool {
+    fn default() -> Self {
+        SpellSchool::Air
+    }
+}
+
+impl SpellSchool {
+    pub const ALL: [SpellSchool; 4] = [
+        SpellSchool::Air,
+        SpellSchool::Earth,
+        SpellSchool::Fire,
+        SpellSchool::Water,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SpellSchool::Air => "Воздух",
+            SpellSchool::Earth => "Земля",
+            SpellSchool::Fire => "Огонь",
+            SpellSchool::Water => "Вода",
+        }
+    }
+
+    /// Code of the secondary skill that masters the school.
+    pub fn skill_code(self) -> &'static str {
+        match self {
+            SpellSchool::Air => "air_magic",
+            SpellSchool::Earth => "earth_magic",
+            SpellSchool::Fire => "fire_magic",
+            SpellSchool::Water => "water_magic",
+        }
+    }
+
+    /// The secondary skill that masters the school, as the game names it.
+    pub fn skill_name(self) -> &'static str {
+        match self {
+            SpellSchool::Air => "Магия Воздуха",
+            SpellSchool::Earth => "Магия Земли",
+            SpellSchool::Fire => "Магия Огня",
+            SpellSchool::Water => "Магия Воды",
+        }
+    }
+
+    /// Schools are stored by their index in [`SpellSchool::ALL`].
+    pub fn from_index(index: usize) -> SpellSchool {
+        SpellSchool::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Spell {
+    pub id: usize,
+    pub name: String,
+    pub school: SpellSchool,
+    pub level: u8,
+    /// Mana it takes without any mastery of the school.
+    pub cost: u16,
+    /// Effect per point of power, 0 for spells that don't scale with it.
+    pub power_factor: u16,
+    pub image: RawImage,
+}
+
+impl PartialEq for Spell {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+/// Keeps spellbooks in the order the window pages through them.
+pub fn sort_spellbook(spellbook: &mut [Spell]) {
+    spellbook.sort_by(|a, b| (a.school, a.level, &a.name).cmp(&(b.school, b.level, &b.name)));
+}
+
+/// What a spell costs and does when a particular hero casts it.
+#[derive(Clone, Copy, Debug)]
+pub struct SpellCast {
+    pub mastery: Option<Mastery>,
+    pub cost: u16,
+    pub effect: u16,
+}
+
+/// An approximation, not the game's per-spell tables: any mastery of the
+/// school takes a fifth off the cost, and each mastery level adds a quarter
+/// to the effect of the hero's power.
+pub fn spell_cast(hero: &Hero, spell: &Spell) -> SpellCast {
+    let mastery = hero
+        .skills
+        .iter()
+        .flatten()
+        .find(|s| s.kind.is(spell.school.skill_code()))
+        .map(|s| s.mastery);
+    let cost = match mastery {
+        Some(_) => spell.cost - spell.cost / 5,
+        None => spell.cost,
+    };
+    let power = stat_value(hero, Stat::Power).total().max(1) as u32;
+    let level = mastery.map_or(0, |m| m.level() as u32);
+    let effect = spell.power_factor as u32 * power * (4 + level) / 4;
+    SpellCast {
+        mastery,
+        cost,
+        effect: effect.min(u16::MAX as u32) as u16,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::test_skill;
+
+    fn fire_spell() -> Spell {
+        Spell {
+            school: SpellSchool::Fire,
+            cost: 10,
+            power_factor: 10,
+            ..Spell::default()
+        }
+    }
+
+    #[test]
+    fn without_the_school_skill() {
+        let hero = Hero {
+            pskills: [1, 1, 5, 1],
+            ..Hero::default()
+        };
+        let cast = spell_cast(&hero, &fire_spell());
+        assert_eq!(cast.mastery, None);
+        assert_eq!(cast.cost, 10);
+        assert_eq!(cast.effect, 50);
+    }
+
+    #[test]
+    fn mastery_cuts_the_cost_and_raises_the_effect() {
+        let mut hero = Hero {
+            pskills: [1, 1, 5, 1],
+            ..Hero::default()
+        };
+        hero.skills[2] = Some(test_skill(1, "fire_magic", Mastery::Expert));
+        let cast = spell_cast(&hero, &fire_spell());
+        assert_eq!(cast.mastery, Some(Mastery::Expert));
+        assert_eq!(cast.cost, 8);
+        assert_eq!(cast.effect, 87);
+    }
+
+    #[test]
+    fn effect_counts_at_least_one_power() {
+        let hero = Hero::default();
+        assert_eq!(spell_cast(&hero, &fire_spell()).effect, 10);
+    }
+}
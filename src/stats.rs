@@ -0,0 +1,326 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::hero::Hero;
+
+/// Luck and morale the game can show; anything past that works like the
+/// nearest end.
+pub const LUCK_MORALE_RANGE: RangeInclusive<i8> = -3..=3;
+
+/// Intelligence raises max mana by these percents with its mastery.
+const INTELLIGENCE: (&str, [u32; 3]) = ("intelligence", [25, 50, 100]);
+
+/// What an item or a specialty adds to each stat, indexed by `Stat as usize`.
+pub type Bonuses = [i8; 6];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stat {
+    Attack,
+    Defence,
+    Power,
+    Knowledge,
+    Luck,
+    Morale,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 6] = [
+        Stat::Attack,
+        Stat::Defence,
+        Stat::Power,
+        Stat::Knowledge,
+        Stat::Luck,
+        Stat::Morale,
+    ];
+    pub const PRIMARY: [Stat; 4] = [Stat::Attack, Stat::Defence, Stat::Power, Stat::Knowledge];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stat::Attack => "Атака",
+            Stat::Defence => "Защита",
+            Stat::Power => "Магия",
+            Stat::Knowledge => "Знания",
+            Stat::Luck => "Удача",
+            Stat::Morale => "Мораль",
+        }
+    }
+}
+
+/// Index of the luck or morale sprite for `value`, from "cursed" at 0 to the
+/// best one at 6.
+pub fn sprite_index(value: i16) -> usize {
+    let (min, max) = (*LUCK_MORALE_RANGE.start(), *LUCK_MORALE_RANGE.end());
+    (value.clamp(min as i16, max as i16) - min as i16) as usize
+}
+
+/// Secondary skills that raise a stat by 1 to 3 with their mastery.
+const SKILL_STATS: [(&str, Stat); 2] = [("luck", Stat::Luck), ("leadership", Stat::Morale)];
+
+/// A stat as the hero screen shows it: the stored value plus everything
+/// that modifies it, each with the name of its source.
+#[derive(Clone, Debug)]
+pub struct StatValue {
+    pub stat: Stat,
+    pub base: i16,
+    pub modifiers: Vec<(String, i16)>,
+}
+
+impl StatValue {
+    pub fn bonus(&self) -> i16 {
+        self.modifiers.iter().map(|(_, value)| value).sum()
+    }
+
+    pub fn total(&self) -> i16 {
+        self.base + self.bonus()
+    }
+
+    /// The total as it works in battle: luck and morale are cut to
+    /// [`LUCK_MORALE_RANGE`].
+    pub fn effective(&self) -> i16 {
+        match self.stat {
+            Stat::Luck | Stat::Morale => {
+                let (min, max) = (*LUCK_MORALE_RANGE.start(), *LUCK_MORALE_RANGE.end());
+                self.total().clamp(min as i16, max as i16)
+            }
+            _ => self.total(),
+        }
+    }
+
+    /// `10 (+2)`, or just `10` when nothing modifies the stat.
+    pub fn label(&self) -> String {
+        match self.bonus() {
+            0 => self.base.to_string(),
+            bonus => format!("{} ({:+})", self.base, bonus),
+        }
+    }
+
+    /// One line per source, for hover texts.
+    pub fn breakdown(&self) -> String {
+        let mut lines = vec![
+            format!("{}: {}", self.stat.name(), self.effective()),
+            format!("Базовое значение: {}", self.base),
+        ];
+        for (source, value) in &self.modifiers {
+            lines.push(format!("{}: {:+}", source, value));
+        }
+        if self.effective() != self.total() {
+            lines.push(format!(
+                "Сумма {} обрезана до {}",
+                self.total(),
+                self.effective()
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Computes `stat` of `hero` from its stored value, worn artifacts, skills,
+/// specialty and, for morale, the make-up of the army.
+pub fn stat_value(hero: &Hero, stat: Stat) -> StatValue {
+    let base = match stat {
+        Stat::Luck => hero.luck as i16,
+        Stat::Morale => hero.morale as i16,
+        _ => hero.pskills[stat as usize] as i16,
+    };
+
+    let mut modifiers = Vec::new();
+    for item in hero.backpack.equipped.iter().flatten() {
+        modifiers.push((item.name.clone(), item.bonuses[stat as usize] as i16));
+    }
+    for skill in hero.skills.iter().flatten() {
+        let raises_stat = SKILL_STATS
+            .iter()
+            .any(|(code, s)| skill.kind.is(code) && *s == stat);
+        if raises_stat {
+            modifiers.push((skill.kind.name.clone(), skill.mastery.level() as i16));
+        }
+    }
+    modifiers.push((
+        hero.spec.name.clone(),
+        hero.spec.bonuses[stat as usize] as i16,
+    ));
+    if stat == Stat::Morale {
+        modifiers.extend(army_morale(hero));
+    }
+    modifiers.retain(|(_, value)| *value != 0);
+
+    StatValue {
+        stat,
+        base,
+        modifiers,
+    }
+}
+
+/// Max mana by the game's rules: 10 per point of knowledge, raised by
+/// Intelligence.
+pub fn auto_mana_max(hero: &Hero) -> u16 {
+    let knowledge = stat_value(hero, Stat::Knowledge).total().max(0) as u32;
+    let (code, percents) = INTELLIGENCE;
+    let percent = hero
+        .skills
+        .iter()
+        .flatten()
+        .find(|s| s.kind.is(code))
+        .map_or(0, |s| percents[s.mastery.level() as usize - 1]);
+    (knowledge * 10 * (100 + percent) / 100).min(u16::MAX as u32) as u16
+}
+
+/// Lowers the current mana to [`auto_mana_max`] unless the maximum is typed
+/// in by hand.
+pub fn clamp_auto_mana(hero: &mut Hero) {
+    if !hero.mana_max_manual {
+        hero.mana_current = hero.mana_current.min(auto_mana_max(hero));
+    }
+}
+
+/// The max mana that is in effect: the typed one if it overrides the rules.
+pub fn mana_max(hero: &Hero) -> u16 {
+    if hero.mana_max_manual {
+        hero.mana_max
+    } else {
+        auto_mana_max(hero)
+    }
+}
+
+/// Armies of one town get +1 morale, each town past the second costs 1, down
+/// to -3, and undead cost everyone else 1 more.
+fn army_morale(hero: &Hero) -> Vec<(String, i16)> {
+    let towns = hero
+        .units
+        .iter()
+        .flatten()
+        .map(|u| u.creature.town.as_str())
+        .collect::<HashSet<_>>();
+
+    let mut modifiers = Vec::new();
+    match towns.len() {
+        0 => {}
+        1 => modifiers.push(("Войска одного города".to_string(), 1)),
+        n => modifiers.push((format!("Войска {} городов", n), -(n as i16 - 2).min(3))),
+    }
+    let undead = hero.units.iter().flatten().any(|u| u.creature.undead);
+    if towns.len() > 1 && undead {
+        modifiers.push(("Нежить в армии".to_string(), -1));
+    }
+    modifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backpack::Item;
+    use crate::skill::{test_skill, Mastery};
+    use crate::unit::{Creature, Unit};
+
+    fn unit(town: &str) -> Option<Unit> {
+        Some(Unit {
+            creature: Creature {
+                town: town.to_string(),
+                undead: town == "Некрополис",
+                ..Creature::default()
+            },
+            count: 1,
+        })
+    }
+
+    fn hero_with_army(towns: &[&str]) -> Hero {
+        let mut hero = Hero::default();
+        for (slot, town) in towns.iter().enumerate() {
+            hero.units[slot] = unit(town);
+        }
+        hero
+    }
+
+    #[test]
+    fn sprite_index_clamps_to_the_range() {
+        assert_eq!(sprite_index(-10), 0);
+        assert_eq!(sprite_index(-3), 0);
+        assert_eq!(sprite_index(0), 3);
+        assert_eq!(sprite_index(3), 6);
+        assert_eq!(sprite_index(10), 6);
+    }
+
+    #[test]
+    fn army_of_one_town_raises_morale() {
+        let hero = hero_with_army(&["Замок", "Замок"]);
+        let morale = stat_value(&hero, Stat::Morale);
+        assert_eq!(morale.modifiers, [("Войска одного города".to_string(), 1)]);
+        assert_eq!(morale.total(), 1);
+    }
+
+    #[test]
+    fn undead_and_mixed_towns_lower_morale() {
+        let hero = hero_with_army(&["Замок", "Оплот", "Некрополис"]);
+        let morale = stat_value(&hero, Stat::Morale);
+        assert_eq!(
+            morale.modifiers,
+            [
+                ("Войска 3 городов".to_string(), -1),
+                ("Нежить в армии".to_string(), -1)
+            ]
+        );
+        assert_eq!(morale.total(), -2);
+    }
+
+    #[test]
+    fn mixed_towns_cost_at_most_three() {
+        let hero = hero_with_army(&["Замок", "Оплот", "Башня", "Инферно", "Темница", "Цитадель"]);
+        assert_eq!(stat_value(&hero, Stat::Morale).total(), -3);
+    }
+
+    #[test]
+    fn luck_skill_adds_its_mastery_and_is_cut_in_battle() {
+        let mut hero = Hero {
+            luck: 1,
+            ..Hero::default()
+        };
+        hero.skills[0] = Some(test_skill(1, "luck", Mastery::Expert));
+        let luck = stat_value(&hero, Stat::Luck);
+        assert_eq!(luck.total(), 4);
+        assert_eq!(luck.effective(), 3);
+        assert_eq!(luck.label(), "1 (+3)");
+    }
+
+    #[test]
+    fn auto_mana_max_counts_items_and_intelligence() {
+        let mut hero = Hero {
+            pskills: [1, 1, 1, 3],
+            ..Hero::default()
+        };
+        hero.backpack.equipped[0] = Some(Item {
+            name: "Том".to_string(),
+            bonuses: [0, 0, 0, 2, 0, 0],
+            ..Item::default()
+        });
+        assert_eq!(auto_mana_max(&hero), 50);
+        hero.skills[0] = Some(test_skill(1, "intelligence", Mastery::Advanced));
+        assert_eq!(auto_mana_max(&hero), 75);
+    }
+
+    #[test]
+    fn manual_max_overrides_the_rules() {
+        let mut hero = Hero {
+            pskills: [1, 1, 1, 1],
+            mana_max: 99,
+            ..Hero::default()
+        };
+        assert_eq!(mana_max(&hero), 10);
+        hero.mana_max_manual = true;
+        assert_eq!(mana_max(&hero), 99);
+    }
+
+    #[test]
+    fn clamp_auto_mana_leaves_manual_max_alone() {
+        let mut hero = Hero {
+            pskills: [1, 1, 1, 1],
+            mana_current: 30,
+            ..Hero::default()
+        };
+        hero.mana_max_manual = true;
+        clamp_auto_mana(&mut hero);
+        assert_eq!(hero.mana_current, 30);
+        hero.mana_max_manual = false;
+        clamp_auto_mana(&mut hero);
+        assert_eq!(hero.mana_current, 10);
+    }
+}
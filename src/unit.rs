@@ -1,2 +1,43 @@
+use std::ops::RangeInclusive;
+
+use crate::utils::RawImage;
+
+pub const CREATURE_SPEEDS: RangeInclusive<u8> = 1..=30;
+/// Speed of new creatures, the same as the column default in migration 0009.
+pub const DEFAULT_CREATURE_SPEED: u8 = 4;
+
+pub const TOWNS: [&str; 10] = [
+    "Замок",
+    "Оплот",
+    "Башня",
+    "Инферно",
+    "Некрополис",
+    "Темница",
+    "Цитадель",
+    "Крепость",
+    "Сопряжение",
+    "Нейтральные",
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Creature {
+    pub id: usize,
+    pub name: String,
+    pub town: String,
+    pub speed: u8,
+    /// Undead lower the morale of the living units they march with.
+    pub undead: bool,
+    pub portrait: RawImage,
+}
+
+impl PartialEq for Creature {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
 #[derive(Clone, Debug)]
-pub struct Unit {}
+pub struct Unit {
+    pub creature: Creature,
+    pub count: u32,
+}
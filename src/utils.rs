@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use eframe::{egui, epi};
 use image::GenericImageView;
 
@@ -12,8 +14,15 @@ pub struct RawImage {
 
 impl RawImage {
     pub fn from_bytes(bytes: &[u8], frame: &mut epi::Frame<'_>) -> RawImage {
+        RawImage::try_from_bytes(bytes, frame).unwrap()
+    }
+
+    pub fn try_from_bytes(
+        bytes: &[u8],
+        frame: &mut epi::Frame<'_>,
+    ) -> Result<RawImage, image::ImageError> {
         let static_image_bytes = bytes;
-        let static_image = image::load_from_memory(static_image_bytes).unwrap();
+        let static_image = image::load_from_memory(static_image_bytes)?;
         let size = (
             static_image.width() as usize,
             static_image.height() as usize,
@@ -31,18 +40,27 @@ impl RawImage {
 
         let bytes = Box::new(Vec::from_iter(bytes.iter().cloned()));
 
-        RawImage {
+        Ok(RawImage {
             texture_id: Some(texture),
             bytes,
             dimensions: (size.0 as f32, size.1 as f32),
-        }
+        })
     }
 
     pub fn load_bytes(&mut self, bytes: &[u8], frame: &mut epi::Frame<'_>) {
-        let new_image = RawImage::from_bytes(bytes, frame);
+        self.try_load_bytes(bytes, frame).unwrap();
+    }
+
+    pub fn try_load_bytes(
+        &mut self,
+        bytes: &[u8],
+        frame: &mut epi::Frame<'_>,
+    ) -> Result<(), image::ImageError> {
+        let new_image = RawImage::try_from_bytes(bytes, frame)?;
         self.dimensions = new_image.dimensions;
         self.bytes = new_image.bytes;
         self.texture_id = new_image.texture_id;
+        Ok(())
     }
 
     pub fn image(&self) -> egui::Image {
@@ -54,6 +72,66 @@ impl RawImage {
     }
 }
 
+/// One texture per distinct picture loaded from the database, so that a
+/// reload gets the same textures back for the pictures that didn't change.
+#[derive(Default)]
+pub struct Textures {
+    /// Each picture with whether it was loaded since the last
+    /// [`Textures::free_unused`].
+    images: HashMap<Vec<u8>, (RawImage, bool)>,
+}
+
+impl Textures {
+    /// Rows created from the edit windows without a picture (or with one that
+    /// no longer decodes) get an empty image rather than failing the load.
+    pub fn load(&mut self, bytes: &[u8], frame: &mut epi::Frame<'_>) -> RawImage {
+        if let Some((image, used)) = self.images.get_mut(bytes) {
+            *used = true;
+            return image.clone();
+        }
+        let image = RawImage::try_from_bytes(bytes, frame).unwrap_or_default();
+        if image.texture_id.is_some() {
+            self.images.insert(bytes.to_vec(), (image.clone(), true));
+        }
+        image
+    }
+
+    /// Frees the textures of the pictures nothing loaded since the last call.
+    pub fn free_unused(&mut self, frame: &mut epi::Frame<'_>) {
+        let allocator = frame.tex_allocator();
+        self.images.retain(|_, (image, used)| {
+            if !*used {
+                if let Some(texture_id) = image.texture_id {
+                    allocator.free(texture_id);
+                }
+            }
+            std::mem::replace(used, false)
+        });
+    }
+}
+
+/// Checks that `bytes` decode as an image before they are stored anywhere.
+pub fn validate_image(bytes: &[u8]) -> Result<(), image::ImageError> {
+    image::load_from_memory(bytes).map(|_| ())
+}
+
+/// Scales and crops the image in `bytes` to exactly `width` x `height`,
+/// re-encoded as PNG.
+pub fn downscale_image(
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, image::ImageError> {
+    let image = image::load_from_memory(bytes)?.resize_to_fill(
+        width,
+        height,
+        image::imageops::FilterType::Triangle,
+    );
+    let mut png = Vec::new();
+    image.write_to(&mut png, image::ImageOutputFormat::Png)?;
+    Ok(png)
+}
+
 pub fn selected_frame_around(ui: &mut egui::Ui, mut rect: egui::Rect) {
     rect = rect.expand(1.0);
     ui.painter()
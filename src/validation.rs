@@ -0,0 +1,237 @@
+use std::fmt;
+
+use crate::hero::Hero;
+use crate::stats::{mana_max, Stat, LUCK_MORALE_RANGE};
+
+/// Highest value a primary skill can have in the game.
+pub const PSKILL_MAX: u8 = 99;
+/// Lowest value of attack, defence, power and knowledge.
+pub const PSKILL_MIN: [u8; 4] = [0, 0, 1, 1];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    PskillOutOfRange { pskill: usize, value: u8 },
+    ManaAboveMax { current: u16, max: u16 },
+    LuckOutOfRange(i8),
+    MoraleOutOfRange(i8),
+    DuplicateSkill { kind: String },
+    SpecOfOtherClass { spec_class: String },
+    EmptyUnit { slot: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::PskillOutOfRange { pskill, value } => write!(
+                f,
+                "{} {} вне пределов {}..{}",
+                Stat::PRIMARY[*pskill].name(),
+                value,
+                PSKILL_MIN[*pskill],
+                PSKILL_MAX
+            ),
+            Violation::ManaAboveMax { current, max } => {
+                write!(f, "Очков магии {} больше максимума {}", current, max)
+            }
+            Violation::LuckOutOfRange(value) => write!(f, "Удача {:+} вне пределов -3..+3", value),
+            Violation::MoraleOutOfRange(value) => {
+                write!(f, "Мораль {:+} вне пределов -3..+3", value)
+            }
+            Violation::DuplicateSkill { kind } => {
+                write!(f, "Навык «{}» взят несколько раз", kind)
+            }
+            Violation::SpecOfOtherClass { spec_class } => {
+                write!(
+                    f,
+                    "Специальность класса «{}» у героя другого класса",
+                    spec_class
+                )
+            }
+            Violation::EmptyUnit { slot } => write!(f, "В отряде {} нет существ", slot + 1),
+        }
+    }
+}
+
+pub fn validate(hero: &Hero) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    for (pskill, value) in hero.pskills.iter().copied().enumerate() {
+        if !(PSKILL_MIN[pskill]..=PSKILL_MAX).contains(&value) {
+            violations.push(Violation::PskillOutOfRange { pskill, value });
+        }
+    }
+    let max = mana_max(hero);
+    if hero.mana_current > max {
+        violations.push(Violation::ManaAboveMax {
+            current: hero.mana_current,
+            max,
+        });
+    }
+    if !LUCK_MORALE_RANGE.contains(&hero.luck) {
+        violations.push(Violation::LuckOutOfRange(hero.luck));
+    }
+    if !LUCK_MORALE_RANGE.contains(&hero.morale) {
+        violations.push(Violation::MoraleOutOfRange(hero.morale));
+    }
+
+    let skills = hero.skills.iter().flatten().collect::<Vec<_>>();
+    for (i, skill) in skills.iter().enumerate() {
+        let first_of_kind = !skills[..i].iter().any(|s| s.kind == skill.kind);
+        if first_of_kind && skills[i + 1..].iter().any(|s| s.kind == skill.kind) {
+            violations.push(Violation::DuplicateSkill {
+                kind: skill.kind.name.clone(),
+            });
+        }
+    }
+
+    if hero.spec.id != 0 && hero.spec.class != hero.character.class {
+        violations.push(Violation::SpecOfOtherClass {
+            spec_class: hero.spec.class.clone(),
+        });
+    }
+
+    for (slot, unit) in hero.units.iter().enumerate() {
+        if unit.as_ref().map_or(false, |u| u.count == 0) {
+            violations.push(Violation::EmptyUnit { slot });
+        }
+    }
+
+    violations
+}
+
+/// Applies `change` to a copy of `hero` and reports the first rule it breaks
+/// that `hero` didn't already break, so that heroes stored before a rule
+/// existed can still be fixed one field at a time.
+pub fn check_change(hero: &Hero, change: impl FnOnce(&mut Hero)) -> Result<(), String> {
+    let before = validate(hero);
+    let mut changed = hero.clone();
+    change(&mut changed);
+    match validate(&changed).into_iter().find(|v| !before.contains(v)) {
+        Some(violation) => Err(violation.to_string()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::{test_skill, Mastery};
+    use crate::spec::Spec;
+    use crate::unit::Unit;
+
+    fn valid_hero() -> Hero {
+        Hero {
+            pskills: [1, 1, 1, 1],
+            ..Hero::default()
+        }
+    }
+
+    #[test]
+    fn valid_hero_has_no_violations() {
+        assert!(validate(&valid_hero()).is_empty());
+    }
+
+    #[test]
+    fn pskills_stay_in_range() {
+        let mut hero = valid_hero();
+        hero.pskills = [100, 1, 1, 0];
+        assert_eq!(
+            validate(&hero),
+            [
+                Violation::PskillOutOfRange {
+                    pskill: 0,
+                    value: 100
+                },
+                Violation::PskillOutOfRange {
+                    pskill: 3,
+                    value: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mana_stays_under_the_max_in_effect() {
+        let mut hero = valid_hero();
+        hero.mana_current = 11;
+        assert_eq!(
+            validate(&hero),
+            [Violation::ManaAboveMax {
+                current: 11,
+                max: 10
+            }]
+        );
+        hero.mana_max_manual = true;
+        hero.mana_max = 20;
+        assert!(validate(&hero).is_empty());
+    }
+
+    #[test]
+    fn luck_and_morale_stay_in_range() {
+        let mut hero = valid_hero();
+        hero.luck = 4;
+        hero.morale = -4;
+        assert_eq!(
+            validate(&hero),
+            [
+                Violation::LuckOutOfRange(4),
+                Violation::MoraleOutOfRange(-4)
+            ]
+        );
+    }
+
+    #[test]
+    fn skill_kinds_are_taken_once() {
+        let mut hero = valid_hero();
+        hero.skills[0] = Some(test_skill(1, "Удача", Mastery::Basic));
+        hero.skills[3] = Some(test_skill(1, "Удача", Mastery::Expert));
+        assert_eq!(
+            validate(&hero),
+            [Violation::DuplicateSkill {
+                kind: "Удача".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn specialty_belongs_to_the_class() {
+        let mut hero = valid_hero();
+        hero.character.class = "Рыцарь".to_string();
+        hero.spec = Spec {
+            id: 1,
+            class: "Маг".to_string(),
+            ..Spec::default()
+        };
+        assert_eq!(
+            validate(&hero),
+            [Violation::SpecOfOtherClass {
+                spec_class: "Маг".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn units_are_not_empty() {
+        let mut hero = valid_hero();
+        hero.units[2] = Some(Unit {
+            creature: Default::default(),
+            count: 0,
+        });
+        assert_eq!(validate(&hero), [Violation::EmptyUnit { slot: 2 }]);
+        assert_eq!(
+            check_change(&valid_hero(), |h| h.units[2] = hero.units[2].clone()),
+            Err("В отряде 3 нет существ".to_string())
+        );
+    }
+
+    #[test]
+    fn check_change_reports_only_new_violations() {
+        let mut hero = valid_hero();
+        hero.luck = 5;
+        assert_eq!(check_change(&hero, |h| h.morale = 1), Ok(()));
+        assert_eq!(
+            check_change(&hero, |h| h.morale = 4),
+            Err("Мораль +4 вне пределов -3..+3".to_string())
+        );
+    }
+}
@@ -1,14 +1,24 @@
-use std::io::Read;
+use std::num::NonZeroU32;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use eframe::egui::*;
 
-use crate::backend::DemoBackend;
-use crate::geometry::{SKILL_BOX, SKILL_IMAGE, SKILL_OFFSET_H, SKILL_TEXT};
-use crate::skill::Skill;
+use crate::backend::HeroBackend;
+use crate::backpack::{Item, ItemKind};
+use crate::character::Character;
+use crate::geometry::{H_SWITCHER_PORTRAIT, SKILL_BOX, SKILL_IMAGE, SKILL_OFFSET_H, SKILL_TEXT};
+use crate::hero::{Hero, HeroSelectButton};
+use crate::hero_class::{ClassKind, HeroClass};
+use crate::player::Player;
+use crate::skill::{Mastery, Skill};
+use crate::skill_offers::{SkillOffer, DEFAULT_SKILL_WEIGHT};
 use crate::spec::Spec;
-use crate::utils::{selected_frame_around, RawImage};
+use crate::spell::{Spell, SpellSchool, SPELL_LEVELS};
+use crate::static_assets::StaticAssets;
+use crate::stats::{mana_max, sprite_index, stat_value, Bonuses, Stat};
+use crate::unit::{Creature, CREATURE_SPEEDS, DEFAULT_CREATURE_SPEED, TOWNS};
+use crate::utils::{downscale_image, selected_frame_around, RawImage};
 use crate::WINDOW_SIZE;
 
 fn toggle_popup(ui: &Ui, widget_response: &Response) -> Option<Id> {
@@ -61,7 +71,28 @@ fn right_to_left() -> Layout {
     Layout::right_to_left().with_cross_align(Align::LEFT)
 }
 
-pub fn show_pskill_popup(ui: &mut Ui, widget_response: Response, value: u8) -> Option<u8> {
+/// `check` tells why a value can't be taken; the popup shows that under the
+/// field and stays open.
+pub fn show_pskill_popup(
+    ui: &mut Ui,
+    widget_response: Response,
+    value: u8,
+    check: impl Fn(u8) -> Result<(), String>,
+) -> Option<u8> {
+    show_number_popup(ui, widget_response, value, |v| check(*v))
+}
+
+pub fn show_unit_count_popup(ui: &mut Ui, widget_response: Response, value: u32) -> Option<u32> {
+    let value = NonZeroU32::new(value).unwrap_or(NonZeroU32::new(1).unwrap());
+    show_number_popup(ui, widget_response, value, |_| Ok(())).map(NonZeroU32::get)
+}
+
+fn show_number_popup<T: FromStr + ToString>(
+    ui: &mut Ui,
+    widget_response: Response,
+    value: T,
+    check: impl Fn(&T) -> Result<(), String>,
+) -> Option<T> {
     let popup_id = toggle_popup(ui, &widget_response)?;
 
     let mut return_val = None;
@@ -71,16 +102,19 @@ pub fn show_pskill_popup(ui: &mut Ui, widget_response: Response, value: u8) -> O
         .get_temp_mut_or_insert_with(popup_id, || value.to_string())
         .clone();
 
-    let mut layouter = type_checking_layouter::<u8>;
+    let mut layouter = type_checking_layouter::<T>;
     let pos = widget_response.rect.left_bottom();
     let width = widget_response.rect.width();
     let inner = popup_ui(ui, popup_id, pos, width, |ui| {
         let edit = TextEdit::singleline(&mut mem_val).layouter(&mut layouter);
         ui.add(edit).request_focus();
         ui.memory().data.insert_temp(popup_id, mem_val.clone());
+        let checked = mem_val.parse::<T>().map(|v| check(&v).map(|_| v));
+        if let Ok(Err(e)) = &checked {
+            ui.colored_label(Color32::RED, e);
+        }
         if ui.button("   ✅").clicked() || ui.input().key_pressed(Key::Enter) {
-            let parsed_value = mem_val.parse::<u8>();
-            if let Ok(new_value) = parsed_value {
+            if let Ok(Ok(new_value)) = checked {
                 return_val = Some(new_value);
             }
         };
@@ -96,7 +130,13 @@ pub fn show_pskill_popup(ui: &mut Ui, widget_response: Response, value: u8) -> O
     return_val
 }
 
-pub fn show_xp_popup(ui: &mut Ui, widget_response: Response, value: u16) -> Option<u16> {
+/// `check` works as in [`show_pskill_popup`].
+pub fn show_xp_popup(
+    ui: &mut Ui,
+    widget_response: Response,
+    value: u32,
+    check: impl Fn(u32) -> Result<(), String>,
+) -> Option<u32> {
     let popup_id = toggle_popup(ui, &widget_response)?;
 
     let mut return_val = None;
@@ -106,7 +146,7 @@ pub fn show_xp_popup(ui: &mut Ui, widget_response: Response, value: u16) -> Opti
         .get_temp_mut_or_insert_with(popup_id, || value.to_string())
         .clone();
 
-    let mut layouter = type_checking_layouter::<u16>;
+    let mut layouter = type_checking_layouter::<u32>;
     let pos = SKILL_IMAGE.right_top() + vec2(4., -6.);
     let width = SKILL_TEXT.width();
     let inner = popup_ui(ui, popup_id, pos, width, |ui| {
@@ -122,9 +162,12 @@ pub fn show_xp_popup(ui: &mut Ui, widget_response: Response, value: u16) -> Opti
         let edit = TextEdit::singleline(&mut mem_val).layouter(&mut layouter);
         ui.add(edit).request_focus();
         ui.memory().data.insert_temp(popup_id, mem_val.clone());
+        let checked = mem_val.parse::<u32>().map(|v| check(v).map(|_| v));
+        if let Ok(Err(e)) = &checked {
+            ui.colored_label(Color32::RED, e);
+        }
         if button_response.clicked() || ui.input().key_pressed(Key::Enter) {
-            let parsed_value = mem_val.parse::<u16>();
-            if let Ok(new_value) = parsed_value {
+            if let Ok(Ok(new_value)) = checked {
                 return_val = Some(new_value);
             }
         }
@@ -140,12 +183,16 @@ pub fn show_xp_popup(ui: &mut Ui, widget_response: Response, value: u16) -> Opti
     return_val
 }
 
+/// `max_value` is `None` when the maximum is computed by the game's rules, in
+/// which case it is `auto_max`. The "Авто" box switches between the two.
 pub fn show_mana_popup(
     ui: &mut Ui,
     widget_response: Response,
     current_value: u16,
-    max_value: u16,
-) -> Option<(u16, u16)> {
+    max_value: Option<u16>,
+    auto_max: u16,
+    check: impl Fn(u16, Option<u16>) -> Result<(), String>,
+) -> Option<(u16, Option<u16>)> {
     let popup_id = toggle_popup(ui, &widget_response)?;
 
     let mut return_val = None;
@@ -154,7 +201,12 @@ pub fn show_mana_popup(
         .memory()
         .data
         .get_temp_mut_or_insert_with(popup_id, || {
-            (current_value.to_string(), max_value.to_string())
+            let max = max_value.unwrap_or(auto_max);
+            (
+                current_value.to_string(),
+                max.to_string(),
+                max_value.is_none(),
+            )
         })
         .clone();
 
@@ -171,10 +223,13 @@ pub fn show_mana_popup(
             .inner;
         ui.with_layout(right_to_left(), |ui| {
             ui.spacing_mut().item_spacing.x = 2.;
+            if mem_vals.2 {
+                mem_vals.1 = auto_max.to_string();
+            }
             let edit_max = TextEdit::singleline(&mut mem_vals.1)
                 .layouter(&mut max_layouter)
                 .desired_width(36.);
-            ui.add(edit_max);
+            ui.add_enabled(!mem_vals.2, edit_max);
 
             ui.label("/");
 
@@ -203,14 +258,26 @@ pub fn show_mana_popup(
                 .layouter(&mut current_layouter)
                 .desired_width(36.);
             ui.add(edit_current);
-
-            ui.memory().data.insert_temp(popup_id, mem_vals.clone());
         });
+        ui.checkbox(&mut mem_vals.2, "Авто")
+            .on_hover_text("10 за каждое очко знаний и бонус навыка «Интеллект»");
+        ui.memory().data.insert_temp(popup_id, mem_vals.clone());
+
+        let max = if mem_vals.2 {
+            Ok(None)
+        } else {
+            mem_vals.1.parse::<u16>().map(Some)
+        };
+        let checked = match (mem_vals.0.parse::<u16>(), max) {
+            (Ok(current), Ok(max)) => Some(check(current, max).map(|_| (current, max))),
+            _ => None,
+        };
+        if let Some(Err(e)) = &checked {
+            ui.colored_label(Color32::RED, e);
+        }
         if button_response.clicked() || ui.input().key_pressed(Key::Enter) {
-            if let (Ok(current), Ok(max)) = (mem_vals.0.parse::<u16>(), mem_vals.1.parse::<u16>()) {
-                if max > current {
-                    return_val = Some((current, max));
-                }
+            if let Some(Ok(new_values)) = checked {
+                return_val = Some(new_values);
             }
         };
     });
@@ -220,7 +287,7 @@ pub fn show_mana_popup(
         || return_val.is_some();
     if close_condition {
         ui.memory().close_popup();
-        ui.memory().data.remove::<String>(popup_id);
+        ui.memory().data.remove::<(String, String, bool)>(popup_id);
     }
     return_val
 }
@@ -287,6 +354,20 @@ pub fn show_selection_window(
     show_closable_window(ui, Some(response), name, false, |ui, _| add_contents(ui))
 }
 
+/// A window asking `question` that opens from `response`; true once the
+/// answer is yes.
+pub fn show_confirm_window(ui: &mut Ui, response: Response, name: &str, question: &str) -> bool {
+    let mut confirmed = false;
+    show_closable_window(ui, Some(response), name, false, |ui, close_window| {
+        ui.label(question);
+        ui.horizontal(|ui| {
+            confirmed = ui.button("Да").clicked();
+            *close_window = confirmed || ui.button("Нет").clicked();
+        });
+    });
+    confirmed
+}
+
 pub fn show_selectable_block(
     ui: &mut Ui,
     image: &RawImage,
@@ -338,15 +419,94 @@ pub fn show_selectable_block_no_edit(
     .inner
 }
 
+/// Window with the skills offered on level-up. Returns the index of the
+/// picked offer, or `Some(None)` once the window is closed without a pick.
+pub fn show_skill_offers_window(ui: &mut Ui, offers: &[SkillOffer]) -> Option<Option<usize>> {
+    let mut is_open = true;
+    let mut picked = None;
+    let window = Window::new("Новый навык")
+        .open(&mut is_open)
+        .collapsible(false)
+        .resizable(false);
+    window.show(ui.ctx(), |ui| {
+        ui.set_width(SKILL_BOX.width());
+        for (i, offer) in offers.iter().enumerate() {
+            let text = offer.skill.title();
+            if show_selectable_block_no_edit(ui, &offer.skill.image, text, false).clicked() {
+                picked = Some(i);
+            }
+        }
+    });
+
+    if picked.is_some() {
+        Some(picked)
+    } else if !is_open || ui.input().key_pressed(Key::Escape) {
+        Some(None)
+    } else {
+        None
+    }
+}
+
+/// Image button that replaces `image` with a file picked through `rfd`. Files
+/// that don't decode are rejected with an inline error instead of a panic.
+/// Returns true when a new image was loaded.
+fn show_image_picker(ui: &mut Ui, image: &mut RawImage, frame: &mut eframe::epi::Frame) -> bool {
+    let error_id = ui.make_persistent_id("image_error");
+    let mut changed = false;
+    ui.vertical(|ui| {
+        let response = if image.texture_id.is_some() {
+            ui.add(image.image_button())
+        } else {
+            ui.button("Выбрать…")
+        };
+        if response.clicked() {
+            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                let loaded = std::fs::read(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| {
+                        image
+                            .try_load_bytes(&bytes, frame)
+                            .map_err(|e| e.to_string())
+                    });
+                changed = loaded.is_ok();
+                match loaded {
+                    Ok(()) => ui.memory().data.remove::<String>(error_id),
+                    Err(e) => ui.memory().data.insert_temp(error_id, e),
+                }
+            }
+        }
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+    });
+    changed
+}
+
+/// One grid row per stat. Luck and morale are limited to what their
+/// sprites can show.
+fn show_bonuses_editor(ui: &mut Ui, bonuses: &mut Bonuses) {
+    for (stat, bonus) in Stat::ALL.into_iter().zip(bonuses) {
+        let range = match stat {
+            Stat::Luck | Stat::Morale => -3..=3,
+            _ => -99..=99,
+        };
+        ui.label(stat.name());
+        ui.add(DragValue::new(bonus).clamp_range(range));
+        ui.end_row();
+    }
+}
+
 pub fn show_skill_edit_window(
     ui: &mut Ui,
     skill: Option<Option<Skill>>,
-    backend: &mut DemoBackend,
+    backend: &mut dyn HeroBackend,
     frame: &mut eframe::epi::Frame,
 ) {
     let force_open = skill.is_some();
     show_closable_window(ui, None, "skill_edit", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
         let (mut skill_in_edit, is_new_skill) = if let Some(skill) = skill {
+            ui.memory().data.remove::<String>(error_id);
             let is_new = skill.is_none();
             (skill.unwrap_or_default(), is_new)
         } else {
@@ -364,43 +524,47 @@ pub fn show_skill_edit_window(
             }
 
             ui.label("Название");
-            ui.text_edit_singleline(&mut skill_in_edit.name);
+            ui.text_edit_singleline(&mut skill_in_edit.kind.name);
             ui.end_row();
 
-            ui.label("Ступень");
-            ui.add(DragValue::new(&mut skill_in_edit.level).clamp_range(1..=3));
+            ui.label("Уровень");
+            ComboBox::from_id_source("mastery")
+                .selected_text(skill_in_edit.mastery.name())
+                .show_ui(ui, |ui| {
+                    for mastery in Mastery::ALL {
+                        ui.selectable_value(&mut skill_in_edit.mastery, mastery, mastery.name());
+                    }
+                });
             ui.end_row();
 
             ui.label("Картинка");
-            if ui.add(skill_in_edit.image.image_button()).clicked() {
-                if let Some(path) = rfd::FileDialog::new().pick_file() {
-                    let bytes = std::fs::File::open(path)
-                        .unwrap()
-                        .bytes()
-                        .map(|b| b.unwrap())
-                        .collect::<Vec<_>>();
-                    skill_in_edit.image.load_bytes(&bytes, frame);
-                }
-            };
+            show_image_picker(ui, &mut skill_in_edit.image, frame);
             ui.end_row()
         });
 
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
         if ui.button("🆗").clicked() {
-            if is_new_skill {
+            let result = if is_new_skill {
                 backend.create_skill(
-                    &skill_in_edit.name,
-                    skill_in_edit.level,
+                    &skill_in_edit.kind.name,
+                    skill_in_edit.mastery,
                     &skill_in_edit.image.bytes,
-                );
+                )
             } else {
                 backend.modify_skill(
                     skill_in_edit.id,
-                    &skill_in_edit.name,
-                    skill_in_edit.level,
+                    &skill_in_edit.kind.name,
+                    skill_in_edit.mastery,
                     &skill_in_edit.image.bytes,
-                );
+                )
+            };
+            match result {
+                Ok(()) => *close_window = true,
+                Err(e) => ui.memory().data.insert_temp(error_id, e),
             }
-            *close_window = true;
         }
 
         ui.memory()
@@ -412,22 +576,31 @@ pub fn show_skill_edit_window(
 pub fn show_spec_edit_window(
     ui: &mut Ui,
     spec: Option<Option<Spec>>,
-    backend: &mut DemoBackend,
+    backend: &mut dyn HeroBackend,
     frame: &mut eframe::epi::Frame,
 ) {
     let force_open = spec.is_some();
     show_closable_window(ui, None, "spec_edit", force_open, |ui, close_window| {
-        let mut spec_in_edit = if let Some(spec) = spec {
-            spec.unwrap_or_default()
+        let error_id = ui.id().with("error");
+        let (mut spec_in_edit, is_new_spec) = if let Some(spec) = spec {
+            ui.memory().data.remove::<String>(error_id);
+            let is_new = spec.is_none();
+            (spec.unwrap_or_default(), is_new)
         } else {
             ui.memory()
                 .data
-                .get_temp_mut_or_default::<Spec>(ui.id())
+                .get_temp_mut_or_default::<(Spec, bool)>(ui.id())
                 .clone()
         };
 
         let classes = backend.get_classes();
         Grid::new("grid").num_columns(2).show(ui, |ui| {
+            if !is_new_spec {
+                ui.label("ID");
+                ui.label(&spec_in_edit.id.to_string());
+                ui.end_row();
+            }
+
             ui.label("Название");
             ui.text_edit_singleline(&mut spec_in_edit.name);
             ui.end_row();
@@ -438,34 +611,748 @@ pub fn show_spec_edit_window(
                     .selected_text(&spec_in_edit.class)
                     .show_ui(ui, |ui| {
                         for class in classes {
-                            ui.selectable_value(&mut spec_in_edit.class, class.to_string(), &class);
+                            let name = class.name;
+                            ui.selectable_value(&mut spec_in_edit.class, name.clone(), name);
                         }
                     });
             }
             ui.end_row();
 
+            show_bonuses_editor(ui, &mut spec_in_edit.bonuses);
+
             ui.label("Картинка");
-            if ui.add(spec_in_edit.image.image_button()).clicked() {
-                if let Some(path) = rfd::FileDialog::new().pick_file() {
-                    let bytes = std::fs::File::open(path)
-                        .unwrap()
-                        .bytes()
-                        .map(|b| b.unwrap())
-                        .collect::<Vec<_>>();
-                    spec_in_edit.image.load_bytes(&bytes, frame);
-                }
-            }
+            show_image_picker(ui, &mut spec_in_edit.image, frame);
         });
 
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
         if ui.button("🆗").clicked() {
-            backend.create_or_modify_spec(
+            let result = backend.create_or_modify_spec(
+                (!is_new_spec).then(|| spec_in_edit.id),
                 &spec_in_edit.name,
                 &spec_in_edit.class,
+                spec_in_edit.bonuses,
                 &spec_in_edit.image.bytes,
             );
-            *close_window = true;
+            match result {
+                Ok(()) => *close_window = true,
+                Err(e) => ui.memory().data.insert_temp(error_id, e),
+            }
+        }
+
+        ui.memory()
+            .data
+            .insert_temp(ui.id(), (spec_in_edit, is_new_spec));
+    });
+}
+
+pub fn show_creature_edit_window(
+    ui: &mut Ui,
+    creature: Option<Option<Creature>>,
+    backend: &mut dyn HeroBackend,
+    frame: &mut eframe::epi::Frame,
+) {
+    let force_open = creature.is_some();
+    show_closable_window(ui, None, "creature_edit", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
+        let (mut creature_in_edit, is_new_creature) = if let Some(creature) = creature {
+            ui.memory().data.remove::<String>(error_id);
+            let is_new = creature.is_none();
+            let creature = creature.unwrap_or_else(|| Creature {
+                speed: DEFAULT_CREATURE_SPEED,
+                ..Default::default()
+            });
+            (creature, is_new)
+        } else {
+            ui.memory()
+                .data
+                .get_temp_mut_or_default::<(Creature, bool)>(ui.id())
+                .clone()
+        };
+
+        Grid::new("grid").num_columns(2).show(ui, |ui| {
+            if !is_new_creature {
+                ui.label("ID");
+                ui.label(&creature_in_edit.id.to_string());
+                ui.end_row();
+            }
+
+            ui.label("Название");
+            ui.text_edit_singleline(&mut creature_in_edit.name);
+            ui.end_row();
+
+            ui.label("Город");
+            ComboBox::from_id_source("town")
+                .selected_text(&creature_in_edit.town)
+                .show_ui(ui, |ui| {
+                    for town in TOWNS {
+                        ui.selectable_value(&mut creature_in_edit.town, town.to_string(), town);
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Скорость");
+            ui.add(DragValue::new(&mut creature_in_edit.speed).clamp_range(CREATURE_SPEEDS));
+            ui.end_row();
+
+            ui.label("Нежить");
+            ui.checkbox(&mut creature_in_edit.undead, "");
+            ui.end_row();
+
+            ui.label("Портрет");
+            show_image_picker(ui, &mut creature_in_edit.portrait, frame);
+        });
+
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        if ui.button("🆗").clicked() {
+            let result = backend.create_or_modify_creature(
+                (!is_new_creature).then(|| creature_in_edit.id),
+                &creature_in_edit.name,
+                &creature_in_edit.town,
+                creature_in_edit.speed,
+                creature_in_edit.undead,
+                &creature_in_edit.portrait.bytes,
+            );
+            match result {
+                Ok(()) => *close_window = true,
+                Err(e) => ui.memory().data.insert_temp(error_id, e),
+            }
+        }
+
+        ui.memory()
+            .data
+            .insert_temp(ui.id(), (creature_in_edit, is_new_creature));
+    });
+}
+
+pub fn show_item_edit_window(
+    ui: &mut Ui,
+    item: Option<Option<Item>>,
+    backend: &mut dyn HeroBackend,
+    frame: &mut eframe::epi::Frame,
+) {
+    let force_open = item.is_some();
+    show_closable_window(ui, None, "item_edit", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
+        let (mut item_in_edit, is_new_item) = if let Some(item) = item {
+            ui.memory().data.remove::<String>(error_id);
+            let is_new = item.is_none();
+            (item.unwrap_or_default(), is_new)
+        } else {
+            ui.memory()
+                .data
+                .get_temp_mut_or_default::<(Item, bool)>(ui.id())
+                .clone()
+        };
+
+        Grid::new("grid").num_columns(2).show(ui, |ui| {
+            if !is_new_item {
+                ui.label("ID");
+                ui.label(&item_in_edit.id.to_string());
+                ui.end_row();
+            }
+
+            ui.label("Название");
+            ui.text_edit_singleline(&mut item_in_edit.name);
+            ui.end_row();
+
+            ui.label("Слот");
+            ComboBox::from_id_source("kind")
+                .selected_text(item_in_edit.kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in ItemKind::ALL {
+                        ui.selectable_value(&mut item_in_edit.kind, kind, kind.name());
+                    }
+                });
+            ui.end_row();
+
+            show_bonuses_editor(ui, &mut item_in_edit.bonuses);
+
+            ui.label("Картинка");
+            show_image_picker(ui, &mut item_in_edit.image, frame);
+        });
+
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        if ui.button("🆗").clicked() {
+            let result = backend.create_or_modify_item(
+                (!is_new_item).then(|| item_in_edit.id),
+                &item_in_edit.name,
+                item_in_edit.kind,
+                item_in_edit.bonuses,
+                &item_in_edit.image.bytes,
+            );
+            match result {
+                Ok(()) => *close_window = true,
+                Err(e) => ui.memory().data.insert_temp(error_id, e),
+            }
         }
 
-        ui.memory().data.insert_temp(ui.id(), spec_in_edit);
+        ui.memory()
+            .data
+            .insert_temp(ui.id(), (item_in_edit, is_new_item));
     });
 }
+
+/// Name, class and the two portraits of a new hero of `player_id`;
+/// `classes` come from the backend.
+pub fn show_hero_create_window(
+    ui: &mut Ui,
+    force_open: bool,
+    classes: &[HeroClass],
+    player_id: usize,
+    backend: &mut dyn HeroBackend,
+    frame: &mut eframe::epi::Frame,
+) {
+    show_closable_window(ui, None, "hero_create", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
+        let mut character = if force_open {
+            ui.memory().data.remove::<String>(error_id);
+            Character {
+                class: classes.first().map(|c| c.name.clone()).unwrap_or_default(),
+                ..Default::default()
+            }
+        } else {
+            ui.memory()
+                .data
+                .get_temp_mut_or_default::<Character>(ui.id())
+                .clone()
+        };
+
+        Grid::new("grid").num_columns(2).show(ui, |ui| {
+            ui.label("Имя");
+            ui.text_edit_singleline(&mut character.name);
+            ui.end_row();
+
+            ui.label("Класс");
+            ComboBox::from_id_source("class")
+                .selected_text(&character.class)
+                .show_ui(ui, |ui| {
+                    for class in classes {
+                        let name = class.name.as_str();
+                        ui.selectable_value(&mut character.class, name.to_string(), name);
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Портрет");
+            show_image_picker(ui, &mut character.portrait, frame);
+            ui.end_row();
+
+            ui.label("Маленький портрет");
+            show_image_picker(ui, &mut character.portrait_small, frame);
+        });
+
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        if ui.button("🆗").clicked() {
+            match backend.create_hero(&character, player_id) {
+                Ok(()) => *close_window = true,
+                Err(e) => ui.memory().data.insert_temp(error_id, e),
+            }
+        }
+
+        ui.memory().data.insert_temp(ui.id(), character);
+    });
+}
+
+/// Both portraits of a hero. `hero` is `Some` on the frame the window is
+/// opened; with "Уменьшить большой" on, the small portrait is cut from the
+/// large one at the switcher size. Returns the large and the small portrait
+/// once they are saved.
+pub fn show_portrait_edit_window(
+    ui: &mut Ui,
+    hero: Option<(usize, &Character)>,
+    backend: &mut dyn HeroBackend,
+    frame: &mut eframe::epi::Frame,
+) -> Option<(RawImage, RawImage)> {
+    let mut saved = None;
+    let force_open = hero.is_some();
+    show_closable_window(
+        ui,
+        None,
+        "Портреты",
+        force_open,
+        |ui, close_window| {
+            let error_id = ui.id().with("error");
+            let (hero_id, mut portrait, mut portrait_small, mut downscale) =
+                if let Some((hero_id, character)) = hero {
+                    ui.memory().data.remove::<String>(error_id);
+                    let portrait = character.portrait.clone();
+                    (hero_id, portrait, character.portrait_small.clone(), false)
+                } else {
+                    ui.memory()
+                        .data
+                        .get_temp_mut_or_default::<(usize, RawImage, RawImage, bool)>(ui.id())
+                        .clone()
+                };
+
+            let mut regenerate = false;
+            Grid::new("grid").num_columns(2).show(ui, |ui| {
+                ui.label("Портрет");
+                regenerate |= show_image_picker(ui, &mut portrait, frame);
+                ui.end_row();
+
+                ui.label("Маленький портрет");
+                ui.vertical(|ui| {
+                    if !downscale {
+                        show_image_picker(ui, &mut portrait_small, frame);
+                    } else {
+                        ui.add(portrait_small.image());
+                    }
+                    regenerate |= ui.checkbox(&mut downscale, "Уменьшить большой").changed();
+                });
+            });
+
+            if downscale && regenerate {
+                let size = H_SWITCHER_PORTRAIT.size();
+                let small = downscale_image(&portrait.bytes, size.x as u32, size.y as u32)
+                    .and_then(|bytes| RawImage::try_from_bytes(&bytes, frame));
+                match small {
+                    Ok(small) => portrait_small = small,
+                    Err(e) => ui.memory().data.insert_temp(error_id, e.to_string()),
+                }
+            }
+
+            if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+                ui.colored_label(Color32::RED, error);
+            }
+
+            if ui.button("🆗").clicked() {
+                match backend.set_hero_portraits(hero_id, &portrait, &portrait_small) {
+                    Ok(()) => {
+                        *close_window = true;
+                        saved = Some((portrait.clone(), portrait_small.clone()));
+                    }
+                    Err(e) => ui.memory().data.insert_temp(error_id, e),
+                }
+            }
+
+            ui.memory()
+                .data
+                .insert_temp(ui.id(), (hero_id, portrait, portrait_small, downscale));
+        },
+    );
+    saved
+}
+
+/// Catalogue entry of a class: its town, growth chances and skill weights.
+/// Existing classes can also be deleted from here.
+pub fn show_class_edit_window(
+    ui: &mut Ui,
+    class: Option<Option<HeroClass>>,
+    backend: &mut dyn HeroBackend,
+) {
+    let force_open = class.is_some();
+    show_closable_window(ui, None, "class_edit", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
+        let (mut class_in_edit, is_new_class) = if let Some(class) = class {
+            ui.memory().data.remove::<String>(error_id);
+            let is_new = class.is_none();
+            let class = class.unwrap_or_else(|| HeroClass {
+                town: TOWNS[0].to_string(),
+                low_level_growth: [25; 4],
+                high_level_growth: [25; 4],
+                ..Default::default()
+            });
+            (class, is_new)
+        } else {
+            ui.memory()
+                .data
+                .get_temp_mut_or_default::<(HeroClass, bool)>(ui.id())
+                .clone()
+        };
+
+        Grid::new("grid").num_columns(5).show(ui, |ui| {
+            if !is_new_class {
+                ui.label("ID");
+                ui.label(&class_in_edit.id.to_string());
+                ui.end_row();
+            }
+
+            ui.label("Название");
+            ui.text_edit_singleline(&mut class_in_edit.name);
+            ui.end_row();
+
+            ui.label("Город");
+            ComboBox::from_id_source("town")
+                .selected_text(&class_in_edit.town)
+                .show_ui(ui, |ui| {
+                    for town in TOWNS {
+                        ui.selectable_value(&mut class_in_edit.town, town.to_string(), town);
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Тип");
+            ComboBox::from_id_source("kind")
+                .selected_text(class_in_edit.kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in ClassKind::ALL {
+                        ui.selectable_value(&mut class_in_edit.kind, kind, kind.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Рост, %");
+            for stat in Stat::PRIMARY {
+                ui.label(stat.name());
+            }
+            ui.end_row();
+
+            ui.label("2-9 уровни");
+            for growth in &mut class_in_edit.low_level_growth {
+                ui.add(DragValue::new(growth).clamp_range(0..=100));
+            }
+            ui.end_row();
+
+            ui.label("С 10 уровня");
+            for growth in &mut class_in_edit.high_level_growth {
+                ui.add(DragValue::new(growth).clamp_range(0..=100));
+            }
+            ui.end_row();
+        });
+
+        ui.separator();
+        ui.label(format!(
+            "Шансы навыков (остальные: {})",
+            DEFAULT_SKILL_WEIGHT
+        ));
+        let skill_kinds = backend.get_skill_kinds().unwrap_or_default();
+        let mut removed = None;
+        Grid::new("skill_weights").num_columns(3).show(ui, |ui| {
+            for (i, (kind, weight)) in class_in_edit.skill_weights.iter_mut().enumerate() {
+                ComboBox::from_id_source(("skill_weight", i))
+                    .selected_text(&kind.name)
+                    .show_ui(ui, |ui| {
+                        for k in &skill_kinds {
+                            ui.selectable_value(kind, k.clone(), &k.name);
+                        }
+                    });
+                ui.add(DragValue::new(weight).clamp_range(0..=99));
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = removed {
+            class_in_edit.skill_weights.remove(i);
+        }
+        if ui.small_button("➕").clicked() {
+            class_in_edit
+                .skill_weights
+                .push((Default::default(), DEFAULT_SKILL_WEIGHT));
+        }
+
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("🆗").clicked() {
+                match backend.create_or_modify_class(&class_in_edit, is_new_class) {
+                    Ok(()) => *close_window = true,
+                    Err(e) => ui.memory().data.insert_temp(error_id, e),
+                }
+            }
+            if !is_new_class && ui.button("Удалить").clicked() {
+                match backend.delete_class(class_in_edit.id) {
+                    Ok(()) => *close_window = true,
+                    Err(e) => ui.memory().data.insert_temp(error_id, e),
+                }
+            }
+        });
+
+        ui.memory()
+            .data
+            .insert_temp(ui.id(), (class_in_edit, is_new_class));
+    });
+}
+
+/// Name and crest colour of a player. Players without heroes can also be
+/// deleted from here.
+pub fn show_player_edit_window(
+    ui: &mut Ui,
+    player: Option<Option<Player>>,
+    backend: &mut dyn HeroBackend,
+) {
+    let force_open = player.is_some();
+    show_closable_window(ui, None, "player_edit", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
+        let (mut player_in_edit, is_new_player) = if let Some(player) = player {
+            ui.memory().data.remove::<String>(error_id);
+            let is_new = player.is_none();
+            (player.unwrap_or_default(), is_new)
+        } else {
+            ui.memory()
+                .data
+                .get_temp_mut_or_default::<(Player, bool)>(ui.id())
+                .clone()
+        };
+
+        Grid::new("grid").num_columns(2).show(ui, |ui| {
+            if !is_new_player {
+                ui.label("ID");
+                ui.label(&player_in_edit.id.to_string());
+                ui.end_row();
+            }
+
+            ui.label("Имя");
+            ui.text_edit_singleline(&mut player_in_edit.name);
+            ui.end_row();
+
+            ui.label("Цвет");
+            ui.color_edit_button_srgb(&mut player_in_edit.color);
+            ui.end_row();
+        });
+
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("🆗").clicked() {
+                match backend.create_or_modify_player(&player_in_edit, is_new_player) {
+                    Ok(()) => *close_window = true,
+                    Err(e) => ui.memory().data.insert_temp(error_id, e),
+                }
+            }
+            if !is_new_player && ui.button("Удалить").clicked() {
+                match backend.delete_player(player_in_edit.id) {
+                    Ok(()) => *close_window = true,
+                    Err(e) => ui.memory().data.insert_temp(error_id, e),
+                }
+            }
+        });
+
+        ui.memory()
+            .data
+            .insert_temp(ui.id(), (player_in_edit, is_new_player));
+    });
+}
+
+pub fn show_spell_edit_window(
+    ui: &mut Ui,
+    spell: Option<Option<Spell>>,
+    backend: &mut dyn HeroBackend,
+    frame: &mut eframe::epi::Frame,
+) {
+    let force_open = spell.is_some();
+    show_closable_window(ui, None, "spell_edit", force_open, |ui, close_window| {
+        let error_id = ui.id().with("error");
+        let (mut spell_in_edit, is_new_spell) = if let Some(spell) = spell {
+            ui.memory().data.remove::<String>(error_id);
+            let is_new = spell.is_none();
+            let spell = spell.unwrap_or_else(|| Spell {
+                level: *SPELL_LEVELS.start(),
+                ..Default::default()
+            });
+            (spell, is_new)
+        } else {
+            ui.memory()
+                .data
+                .get_temp_mut_or_default::<(Spell, bool)>(ui.id())
+                .clone()
+        };
+
+        Grid::new("grid").num_columns(2).show(ui, |ui| {
+            if !is_new_spell {
+                ui.label("ID");
+                ui.label(&spell_in_edit.id.to_string());
+                ui.end_row();
+            }
+
+            ui.label("Название");
+            ui.text_edit_singleline(&mut spell_in_edit.name);
+            ui.end_row();
+
+            ui.label("Школа");
+            ComboBox::from_id_source("school")
+                .selected_text(spell_in_edit.school.name())
+                .show_ui(ui, |ui| {
+                    for school in SpellSchool::ALL {
+                        ui.selectable_value(&mut spell_in_edit.school, school, school.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Уровень");
+            ui.add(DragValue::new(&mut spell_in_edit.level).clamp_range(SPELL_LEVELS));
+            ui.end_row();
+
+            ui.label("Стоимость");
+            ui.add(DragValue::new(&mut spell_in_edit.cost).clamp_range(0..=999));
+            ui.end_row();
+
+            ui.label("Сила за очко магии");
+            ui.add(DragValue::new(&mut spell_in_edit.power_factor).clamp_range(0..=999));
+            ui.end_row();
+
+            ui.label("Картинка");
+            show_image_picker(ui, &mut spell_in_edit.image, frame);
+        });
+
+        if let Some(error) = ui.memory().data.get_temp::<String>(error_id) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        if ui.button("🆗").clicked() {
+            match backend.create_or_modify_spell(&spell_in_edit, is_new_spell) {
+                Ok(()) => *close_window = true,
+                Err(e) => ui.memory().data.insert_temp(error_id, e),
+            }
+        }
+
+        ui.memory()
+            .data
+            .insert_temp(ui.id(), (spell_in_edit, is_new_spell));
+    });
+}
+
+/// Two of the `heroes` side by side with the values that differ
+/// highlighted. `selected` holds the ids of the heroes in the left and the
+/// right column. Only those two are fetched from the backend, into `copies`,
+/// and only when `copies` is `None` or holds other heroes.
+pub fn show_hero_comparison_window(
+    ctx: &CtxRef,
+    open: &mut bool,
+    heroes: &[HeroSelectButton],
+    selected: &mut [usize; 2],
+    copies: &mut Option<[Hero; 2]>,
+    backend: &mut dyn HeroBackend,
+    static_assets: &StaticAssets,
+) {
+    Window::new("Сравнение героев").open(open).show(ctx, |ui| {
+        if heroes.len() < 2 {
+            ui.label("Для сравнения нужны два героя");
+            return;
+        }
+        for (side, id) in selected.iter_mut().enumerate() {
+            if !heroes.iter().any(|h| h.id == *id) {
+                *id = heroes[side].id;
+            }
+        }
+
+        Grid::new("comparison")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                for (side, id) in selected.iter_mut().enumerate() {
+                    let name = heroes
+                        .iter()
+                        .find(|h| h.id == *id)
+                        .map_or("", |h| h.name.as_str());
+                    ComboBox::from_id_source(("compared_hero", side))
+                        .selected_text(name)
+                        .show_ui(ui, |ui| {
+                            for hero in heroes {
+                                ui.selectable_value(id, hero.id, hero.name.as_str());
+                            }
+                        });
+                }
+                ui.end_row();
+
+                let stale = copies
+                    .as_ref()
+                    .map_or(true, |c| [c[0].id, c[1].id] != *selected);
+                if stale {
+                    let left = backend.get_hero(selected[0]);
+                    let right = backend.get_hero(selected[1]);
+                    *copies = left.zip(right).map(|(left, right)| [left, right]);
+                }
+                let pair = match copies {
+                    Some(copies) => [&copies[0], &copies[1]],
+                    None => return,
+                };
+                ui.label("");
+                for hero in pair {
+                    ui.add(hero.character.portrait.image());
+                }
+                ui.end_row();
+
+                let plain = |text: String| (text, String::new());
+                compare_row(ui, "Класс", pair.map(|h| plain(h.character.class.clone())));
+                compare_row(ui, "Уровень", pair.map(|h| plain(h.level.to_string())));
+                compare_row(ui, "Опыт", pair.map(|h| plain(h.experience.to_string())));
+                for stat in Stat::PRIMARY {
+                    let values = pair.map(|h| {
+                        let value = stat_value(h, stat);
+                        (value.label(), value.breakdown())
+                    });
+                    compare_row(ui, stat.name(), values);
+                }
+                for (stat, sprites) in [
+                    (Stat::Luck, &static_assets.luck),
+                    (Stat::Morale, &static_assets.morale),
+                ] {
+                    let values = pair.map(|h| stat_value(h, stat));
+                    let images = [&values[0], &values[1]]
+                        .map(|v| Some(&sprites[sprite_index(v.effective())]));
+                    let values = values.map(|v| (format!("{:+}", v.effective()), v.breakdown()));
+                    compare_image_row(ui, stat.name(), images, values);
+                }
+                let mana = pair.map(|h| plain(format!("{}/{}", h.mana_current, mana_max(h))));
+                compare_row(ui, "Очки магии", mana);
+                compare_row(
+                    ui,
+                    "Специальность",
+                    pair.map(|h| plain(h.spec.name.clone())),
+                );
+
+                let mut kinds = Vec::new();
+                for skill in pair.iter().flat_map(|h| h.skills.iter().flatten()) {
+                    if !kinds.contains(&skill.kind) {
+                        kinds.push(skill.kind.clone());
+                    }
+                }
+                for kind in kinds {
+                    let skills = pair.map(|h| h.skills.iter().flatten().find(|s| s.kind == kind));
+                    let images = skills.map(|s| s.map(|s| &s.image));
+                    let values =
+                        skills.map(|s| plain(s.map_or("—", |s| s.mastery.name()).to_string()));
+                    compare_image_row(ui, &kind.name, images, values);
+                }
+            });
+    });
+}
+
+/// A row of the comparison grid; values are `(text, hover text)`.
+fn compare_row(ui: &mut Ui, title: &str, values: [(String, String); 2]) {
+    compare_image_row(ui, title, [None, None], values);
+}
+
+/// The same with each value shown as a block next to its picture, if any.
+fn compare_image_row(
+    ui: &mut Ui,
+    title: &str,
+    images: [Option<&RawImage>; 2],
+    values: [(String, String); 2],
+) {
+    ui.label(title);
+    let differ = values[0].0 != values[1].0;
+    for (image, (text, hover_text)) in images.into_iter().zip(values) {
+        let mut text = RichText::new(text);
+        if differ {
+            text = text.color(Color32::YELLOW);
+        }
+        let response = match image {
+            Some(image) => show_selectable_block_no_edit(ui, image, text, false),
+            None => ui.label(text),
+        };
+        if !hover_text.is_empty() {
+            response.on_hover_text(hover_text);
+        }
+    }
+    ui.end_row();
+}